@@ -1,20 +1,43 @@
 use proc_macro2::TokenStream;
 use quote::ToTokens;
-use syn::{fold::Fold, spanned::Spanned, ItemMod, LitStr};
+use syn::{spanned::Spanned, ItemFn, ItemMod};
 
-use crate::fold::Detours;
+use crate::{
+    fold::{DetourInfo, Detours},
+    parse::{DetourNamesArg, HookModuleArgs, StandaloneHookArgs},
+};
 
-pub fn expand(mod_block: ItemMod, attribute_meta: LitStr) -> Result<TokenStream, syn::Error> {
+pub fn expand(
+    mut mod_block: ItemMod,
+    attribute_meta: HookModuleArgs,
+) -> Result<TokenStream, syn::Error> {
+    let span = mod_block.span();
+    let Some((_, ref mut content)) = mod_block.content.as_mut() else {
+        return Err(syn::Error::new(span, "Could not get content inside `mod`"));
+    };
     let mut detours = Detours::new(attribute_meta);
-    let mut result = detours.fold_item_mod(mod_block);
+    detours.fold_items(content);
+    detours.check()?;
+    content.extend(detours.generate_items());
 
-    let Some((_, ref mut content)) = result.content.as_mut() else {
-        return Err(syn::Error::new(result.span(), "Could not get content inside `mod`"))
-    };
-    content.push(detours.get_module_name_decl());
-    let decls = detours.generate_detour_decls();
-    content.extend(decls);
-    content.push(detours.generate_init_detours());
+    Ok(mod_block.to_token_stream())
+}
 
-    Ok(result.to_token_stream())
+/// Expands a standalone `#[hook(module = "...")]`, which is registered for `init_all` instead of being
+/// part of a `#[hook_module]`
+pub fn expand_hook(item_fn: ItemFn, args: StandaloneHookArgs) -> Result<TokenStream, syn::Error> {
+    let info = DetourInfo::new(
+        args.hook_attr,
+        item_fn.sig.clone(),
+        None,
+        None,
+        None,
+        &item_fn.attrs,
+        DetourNamesArg::DEFAULT,
+    )?;
+    let mut result = item_fn.to_token_stream();
+    for item in info.generate_standalone(&args.module_name) {
+        item.to_tokens(&mut result);
+    }
+    Ok(result)
 }
@@ -1,30 +1,187 @@
 use proc_macro2::TokenStream;
-use quote::quote_spanned;
-use syn::{fold::Fold, spanned::Spanned, Item, ItemFn, LitStr, Signature};
+use quote::{quote_spanned, ToTokens};
+use syn::{
+    ext::IdentExt, fold::Fold, spanned::Spanned, Abi, Attribute, Expr, ExprLit, FnArg, ForeignItem,
+    GenericParam, Ident, ImplItem, Item, ItemFn, ItemImpl, ItemMod, Lit, LitStr, Meta,
+    MetaNameValue, Path, ReturnType, Signature, Type,
+};
 
 use crate::{
     crate_refs,
-    helpers::{fn_arg_names, fn_type},
-    parse::HookAttributeArgs,
+    helpers::{
+        cfg_attrs, ctx_arg_index, doc_attrs, fn_arg_names, fn_output, fn_type, fn_types, Errors,
+        Passing, ReplaceSelf,
+    },
+    parse::{DetourNamesArg, HookArg, HookAttributeArgs, HookModuleArgs},
 };
 
 pub struct Detours {
     module_name: LitStr,
+    load_if_missing: bool,
+    deferred: bool,
+    transactional: bool,
     detours: Vec<DetourInfo>,
+    /// Naming scheme for hooks without a detour name, see `DetourNamesArg`
+    detour_names: String,
+    /// Functions declared in `extern` blocks of the module
+    declarations: Vec<Declaration>,
+    /// Nested modules with hooks, which have their own generated items that this module's chain into
+    submodules: Vec<SubModule>,
+    /// Set for the `Detours` of a nested module
+    is_submodule: bool,
+    errors: Errors,
+}
+
+/// A `mod` nested in the hook module that has hooks itself or in its own nested modules
+pub struct SubModule {
+    pub ident: Ident,
+    /// `#[cfg]`s of the `mod`, put on the calls into it
+    pub cfg_attrs: Vec<Attribute>,
+    pub has_groups: bool,
+}
+
+/// A function declared in an `extern` block of the hook module, e.g. from bindgen,
+/// which `#[hook(lua_settop)]` takes the signature, ABI and symbol of
+#[derive(Clone)]
+pub struct Declaration {
+    pub sig: Signature,
+    pub abi: Abi,
+    /// `#[link_name = "..."]`, the symbol if it's not the function's name
+    pub link_name: Option<LitStr>,
 }
 
 impl Detours {
-    pub fn new(module_name: LitStr) -> Self {
+    pub fn new(module_args: HookModuleArgs) -> Self {
         Self {
-            module_name,
+            module_name: module_args.module_name,
+            load_if_missing: module_args.load_if_missing.is_some(),
+            deferred: module_args.deferred.is_some(),
+            transactional: module_args.transactional.is_some(),
             detours: Vec::new(),
+            detour_names: module_args.detour_names.map_or_else(
+                || DetourNamesArg::DEFAULT.to_string(),
+                |arg| arg.value.value(),
+            ),
+            declarations: Vec::new(),
+            submodules: Vec::new(),
+            is_submodule: false,
+            errors: Errors::default(),
         }
     }
 
+    /// Detours of a nested module, which shares the options of this one
+    fn submodule(&self) -> Self {
+        Self {
+            module_name: self.module_name.clone(),
+            load_if_missing: self.load_if_missing,
+            deferred: self.deferred,
+            transactional: self.transactional,
+            detours: Vec::new(),
+            detour_names: self.detour_names.clone(),
+            declarations: Vec::new(),
+            submodules: Vec::new(),
+            is_submodule: true,
+            errors: Errors::default(),
+        }
+    }
+
+    /// Folds the items of the module, collecting its hooks and those of its nested modules
+    pub fn fold_items(&mut self, items: &mut Vec<Item>) {
+        self.collect_declarations(items);
+        *items = std::mem::take(items)
+            .into_iter()
+            .map(|item| self.fold_item(item))
+            .collect();
+    }
+
+    /// Generates everything for the module's hooks, to append to its items once they're folded and checked
+    pub fn generate_items(&self) -> Vec<Item> {
+        let mut items = vec![self.get_module_name_decl()];
+        items.extend(self.generate_detour_decls());
+        items.extend(self.generate_hooks_table());
+        items.extend(self.generate_init_hooks());
+        items.push(self.generate_init_detours());
+        items.extend(self.generate_toggle_detours());
+        items.extend(self.generate_group_fns());
+        items.extend(self.generate_hook_module_impl());
+        items
+    }
+
+    fn has_groups(&self) -> bool {
+        self.detours
+            .iter()
+            .any(|info| info.hook_attr.group.is_some())
+            || self.submodules.iter().any(|submodule| submodule.has_groups)
+    }
+
+    /// Collects the functions declared in the `extern` blocks of the module's items
+    pub fn collect_declarations(&mut self, items: &[Item]) {
+        for item in items {
+            let Item::ForeignMod(foreign_mod) = item else {
+                continue;
+            };
+            for foreign_item in &foreign_mod.items {
+                let ForeignItem::Fn(foreign_fn) = foreign_item else {
+                    continue;
+                };
+                let link_name = foreign_fn.attrs.iter().find_map(|attr| match &attr.meta {
+                    Meta::NameValue(MetaNameValue {
+                        path,
+                        value:
+                            Expr::Lit(ExprLit {
+                                lit: Lit::Str(link_name),
+                                ..
+                            }),
+                        ..
+                    }) if path.is_ident("link_name") => Some(link_name.clone()),
+                    _ => None,
+                });
+                self.declarations.push(Declaration {
+                    sig: foreign_fn.sig.clone(),
+                    abi: foreign_mod.abi.clone(),
+                    link_name,
+                });
+            }
+        }
+    }
+
+    /// Every error found in the `#[hook]`s, including hooks sharing a detour name or a target
+    pub fn check(&mut self) -> Result<(), syn::Error> {
+        let mut errors = std::mem::take(&mut self.errors);
+        for (i, info) in self.detours.iter().enumerate() {
+            // Hooks with different `#[cfg]`s, e.g. one per platform, may never be compiled together
+            let cfgs = info.cfg_tokens();
+            let earlier: Vec<_> = self.detours[..i]
+                .iter()
+                .filter(|other| other.cfg_tokens() == cfgs)
+                .collect();
+            let detour_name = &info.detour_name;
+            if earlier
+                .iter()
+                .any(|other| other.detour_name == *detour_name)
+            {
+                errors.push(syn::Error::new(
+                    detour_name.span(),
+                    format!("duplicate detour name `{detour_name}`"),
+                ));
+            }
+            let target = info.lookup.target();
+            if let Some(other) = earlier.iter().find(|other| other.lookup.target() == target) {
+                errors.push(syn::Error::new_spanned(
+                    &info.lookup,
+                    format!("this target is already hooked by `{}`", other.detour_name),
+                ));
+            }
+        }
+        errors.into_result()
+    }
+
     pub fn generate_detour_decls(&self) -> Vec<Item> {
         self.detours
             .iter()
-            .map(|info| info.get_static_detour())
+            .flat_map(|info| {
+                std::iter::once(info.get_static_detour()).chain(info.generate_signature_check())
+            })
             .collect()
     }
 
@@ -41,44 +198,564 @@ impl Detours {
         })
     }
 
-    pub fn generate_init_detours(&self) -> Item {
+    /// Returns the `HOOKS` table describing every hook, and registers the module in `HOOK_MODULES`
+    /// ```ignore
+    /// pub static HOOKS: [retour_utils::HookDescriptor; 1] = [..];
+    /// ```
+    pub fn generate_hooks_table(&self) -> Vec<Item> {
+        let krate_name = crate_refs::parent_crate();
+        let hook_count = self.hook_index(self.detours.len());
+        // `init_all` only calls the outermost `init_detours`, which chains into the nested modules'
+        let init = if self.is_submodule {
+            quote::quote! { None }
+        } else {
+            quote::quote! { Some(init_detours) }
+        };
+        let descriptors = self.detours.iter().map(|info| {
+            let cfg_attrs = &info.cfg_attrs;
+            let descriptor = info.generate_descriptor(&self.module_name);
+            quote::quote! { #(#cfg_attrs)* #descriptor }
+        });
+        vec![
+            Item::Verbatim(quote::quote! {
+                #[allow(unused)]
+                pub static HOOKS: [::#krate_name::HookDescriptor; #hook_count] = [#(#descriptors),*];
+            }),
+            Item::Verbatim(quote::quote! {
+                #[::#krate_name::__private::linkme::distributed_slice(::#krate_name::HOOK_MODULES)]
+                #[linkme(crate = ::#krate_name::__private::linkme)]
+                static __HOOK_MODULE: ::#krate_name::ModuleDescriptor = ::#krate_name::ModuleDescriptor {
+                    name: MODULE_NAME,
+                    path: ::core::module_path!(),
+                    hooks: &HOOKS,
+                    init: #init,
+                };
+            }),
+        ]
+    }
+
+    /// Generates the hidden `__init_hooks` and `__toggle_fns`, which handle the hooks of the module and of
+    /// its nested modules, or only those of a group. The other generated functions are built on them
+    pub fn generate_init_hooks(&self) -> Vec<Item> {
         let krate_name = crate_refs::parent_crate();
-        let init_funcs: Vec<Item> = self
+        let detour_krate = crate_refs::retour_crate();
+        let toggle_fn_type = quote::quote! { unsafe fn() -> ::#detour_krate::Result<()> };
+        let detour_names: Vec<_> = self.detours.iter().map(|info| &info.detour_name).collect();
+        let cfg_attrs: Vec<_> = self.detours.iter().map(|info| &info.cfg_attrs).collect();
+        let in_group: Vec<_> = self
             .detours
             .iter()
-            .map(|func| func.generate_detour_init(&self.module_name))
+            .map(|info| match &info.hook_attr.group {
+                Some(group) => {
+                    let group = &group.value;
+                    quote::quote! { group.map_or(true, |group| group == #group) }
+                }
+                None => quote::quote! { group.is_none() },
+            })
             .collect();
-        Item::Verbatim(quote::quote! {
-            pub unsafe fn init_detours() -> Result<(), #krate_name::Error> {
-                #(#init_funcs;)*
+        let init_funcs = self.detours.iter().enumerate().map(|(index, info)| {
+            let index = self.hook_index(index);
+            info.generate_detour_init(quote::quote! { HOOKS[#index] }, self.load_if_missing)
+        });
+        let submodules: Vec<_> = self
+            .submodules
+            .iter()
+            .map(|submodule| &submodule.ident)
+            .collect();
+        let submodule_cfg_attrs: Vec<_> = self
+            .submodules
+            .iter()
+            .map(|submodule| &submodule.cfg_attrs)
+            .collect();
+        vec![
+            Item::Verbatim(quote::quote! {
+                #[doc(hidden)]
+                #[allow(unused_variables)]
+                pub unsafe fn __init_hooks(
+                    group: Option<&str>,
+                    report: &mut ::#krate_name::InitReport,
+                    disable_fns: &mut Vec<#toggle_fn_type>,
+                ) {
+                    #(
+                        #(#cfg_attrs)*
+                        if #in_group {
+                            report.hooks.push(#init_funcs);
+                            disable_fns.push(|| #detour_names.disable());
+                        }
+                    )*
+                    #(
+                        #(#submodule_cfg_attrs)*
+                        #submodules::__init_hooks(group, report, disable_fns);
+                    )*
+                }
+            }),
+            Item::Verbatim(quote::quote! {
+                #[doc(hidden)]
+                #[allow(unused_variables)]
+                pub unsafe fn __toggle_fns(group: Option<&str>, enable: bool, toggle_fns: &mut Vec<#toggle_fn_type>) {
+                    #(
+                        #(#cfg_attrs)*
+                        if #in_group {
+                            if enable {
+                                toggle_fns.push(|| #detour_names.enable());
+                            } else {
+                                toggle_fns.push(|| #detour_names.disable());
+                            }
+                        }
+                    )*
+                    #(
+                        #(#submodule_cfg_attrs)*
+                        #submodules::__toggle_fns(group, enable, toggle_fns);
+                    )*
+                }
+            }),
+        ]
+    }
+
+    pub fn generate_init_detours(&self) -> Item {
+        let krate_name = crate_refs::parent_crate();
+        // Disable every hook that was installed if any of them failed
+        let roll_back = self.transactional.then(|| {
+            quote::quote! {
+                report.roll_back_on_failure(&disable_fns);
+            }
+        });
+        let init_detours_body = quote::quote! {
+            let mut report = ::#krate_name::InitReport::default();
+            let mut disable_fns = Vec::new();
+            __init_hooks(None, &mut report, &mut disable_fns);
+            #roll_back
 
-                Ok(())
+            report.into_result()
+        };
+        if self.deferred {
+            return Item::Verbatim(quote::quote! {
+                pub unsafe fn init_detours() -> Result<#krate_name::InitReport, #krate_name::Error> {
+                    unsafe fn __init_detours() -> Result<#krate_name::InitReport, #krate_name::Error> {
+                        #init_detours_body
+                    }
+                    ::#krate_name::defer_detours(MODULE_NAME, __init_detours)
+                }
+            });
+        }
+        Item::Verbatim(quote::quote! {
+            pub unsafe fn init_detours() -> Result<#krate_name::InitReport, #krate_name::Error> {
+                #init_detours_body
             }
         })
     }
+
+    /// Index in `HOOKS` of the hook at `index`, which is only known once the `#[cfg]`s of the hooks
+    /// before it are evaluated. Passing the number of hooks gives the length of `HOOKS`
+    fn hook_index(&self, index: usize) -> TokenStream {
+        let earlier = &self.detours[..index];
+        if earlier.iter().all(|info| info.cfg_attrs.is_empty()) {
+            return quote::quote! { #index };
+        }
+        let cfg_attrs = earlier.iter().map(|info| &info.cfg_attrs);
+        quote::quote! {{
+            #[allow(unused_mut)]
+            let mut index = 0;
+            #(
+                #(#cfg_attrs)*
+                {
+                    index += 1;
+                }
+            )*
+            index
+        }}
+    }
+
+    /// Generates `enable_detours`, `disable_detours` and `is_enabled` for every `StaticDetour` in the module
+    /// and its nested modules
+    pub fn generate_toggle_detours(&self) -> Vec<Item> {
+        let krate_name = crate_refs::parent_crate();
+        let detour_names: Vec<_> = self.detours.iter().map(|info| &info.detour_name).collect();
+        let cfg_attrs: Vec<_> = self.detours.iter().map(|info| &info.cfg_attrs).collect();
+        let submodules = self.submodules.iter().map(|submodule| &submodule.ident);
+        let submodule_cfg_attrs = self.submodules.iter().map(|submodule| &submodule.cfg_attrs);
+        vec![
+            Item::Verbatim(quote::quote! {
+                pub unsafe fn enable_detours() -> Result<(), #krate_name::Error> {
+                    let mut toggle_fns = Vec::new();
+                    __toggle_fns(None, true, &mut toggle_fns);
+                    ::#krate_name::toggle_detours(&toggle_fns)
+                }
+            }),
+            Item::Verbatim(quote::quote! {
+                pub unsafe fn disable_detours() -> Result<(), #krate_name::Error> {
+                    let mut toggle_fns = Vec::new();
+                    __toggle_fns(None, false, &mut toggle_fns);
+                    ::#krate_name::toggle_detours(&toggle_fns)
+                }
+            }),
+            Item::Verbatim(quote::quote! {
+                pub fn is_enabled() -> bool {
+                    #(
+                        #(#cfg_attrs)*
+                        if #detour_names.is_enabled() {
+                            return true;
+                        }
+                    )*
+                    #(
+                        #(#submodule_cfg_attrs)*
+                        if #submodules::is_enabled() {
+                            return true;
+                        }
+                    )*
+                    false
+                }
+            }),
+        ]
+    }
+
+    /// Generates the `Module` marker type implementing `HookModule` with the module's generated functions
+    pub fn generate_hook_module_impl(&self) -> Vec<Item> {
+        let krate_name = crate_refs::parent_crate();
+        vec![
+            Item::Verbatim(quote::quote! {
+                /// Marker type for this hook module, see [`HookModule`](::#krate_name::HookModule)
+                #[derive(Debug, Clone, Copy, Default)]
+                pub struct Module;
+            }),
+            Item::Verbatim(quote::quote! {
+                impl ::#krate_name::HookModule for Module {
+                    fn module_name(&self) -> &'static str {
+                        MODULE_NAME
+                    }
+
+                    unsafe fn init(&self) -> Result<#krate_name::InitReport, #krate_name::Error> {
+                        init_detours()
+                    }
+
+                    unsafe fn enable(&self) -> Result<(), #krate_name::Error> {
+                        enable_detours()
+                    }
+
+                    unsafe fn disable(&self) -> Result<(), #krate_name::Error> {
+                        disable_detours()
+                    }
+
+                    fn is_enabled(&self) -> bool {
+                        is_enabled()
+                    }
+
+                    fn hooks(&self) -> &'static [::#krate_name::HookDescriptor] {
+                        &HOOKS
+                    }
+                }
+            }),
+        ]
+    }
+
+    /// Generates `init_group`, `enable_group` and `disable_group` if any hook of the module or its nested
+    /// modules has a `group = "..."`
+    pub fn generate_group_fns(&self) -> Vec<Item> {
+        if !self.has_groups() {
+            return Vec::new();
+        }
+        let krate_name = crate_refs::parent_crate();
+        let roll_back = self.transactional.then(|| {
+            quote::quote! {
+                report.roll_back_on_failure(&disable_fns);
+            }
+        });
+        vec![
+            Item::Verbatim(quote::quote! {
+                pub unsafe fn init_group(group: &str) -> Result<#krate_name::InitReport, #krate_name::Error> {
+                    let mut report = ::#krate_name::InitReport::default();
+                    let mut disable_fns = Vec::new();
+                    __init_hooks(Some(group), &mut report, &mut disable_fns);
+                    if report.hooks.is_empty() {
+                        return Err(::#krate_name::Error::UnknownGroup);
+                    }
+                    #roll_back
+
+                    report.into_result()
+                }
+            }),
+            Item::Verbatim(quote::quote! {
+                pub unsafe fn enable_group(group: &str) -> Result<(), #krate_name::Error> {
+                    let mut toggle_fns = Vec::new();
+                    __toggle_fns(Some(group), true, &mut toggle_fns);
+                    if toggle_fns.is_empty() {
+                        return Err(::#krate_name::Error::UnknownGroup);
+                    }
+                    ::#krate_name::toggle_detours(&toggle_fns)
+                }
+            }),
+            Item::Verbatim(quote::quote! {
+                pub unsafe fn disable_group(group: &str) -> Result<(), #krate_name::Error> {
+                    let mut toggle_fns = Vec::new();
+                    __toggle_fns(Some(group), false, &mut toggle_fns);
+                    if toggle_fns.is_empty() {
+                        return Err(::#krate_name::Error::UnknownGroup);
+                    }
+                    ::#krate_name::toggle_detours(&toggle_fns)
+                }
+            }),
+        ]
+    }
 }
 
+/// ABIs retour can detour, `extern` without an ABI string being `"C"`
+const SUPPORTED_ABIS: &[&str] = &[
+    "Rust", "C", "system", "cdecl", "stdcall", "fastcall", "win64", "thiscall",
+];
+
 pub struct DetourInfo {
     pub hook_attr: HookAttributeArgs,
+    /// Name of the `StaticDetour`, given in `#[hook]` or derived from the hook function's name
+    pub detour_name: Ident,
+    /// Given in `#[hook]`, or the symbol with the same name as the hook function
+    pub lookup: HookArg,
+    /// Name of the hook function
+    pub fn_name: Ident,
+    /// Signature of the hooked function, which is the hook function's unless it uses a [`Declaration`]
     pub fn_sig: Signature,
+    /// Set if the hook is a method, `Self` is already replaced by it in `fn_sig`
+    pub self_ty: Option<Type>,
+    /// Trait of the `impl` block the method is in
+    pub trait_path: Option<Path>,
+    /// Argument types of the `StaticDetour`'s function type, and how they're passed to the hook
+    pub arg_types: Vec<(Type, Passing)>,
+    pub output_type: ReturnType,
+    pub output_passing: Passing,
+    /// `#[cfg]`s of the hook function (and its `impl` block), put on everything generated for the hook
+    pub cfg_attrs: Vec<Attribute>,
+    /// Doc comments of the hook function, put on the `StaticDetour`
+    pub doc_attrs: Vec<Attribute>,
+    /// Position of the hook function's `ctx: &HookCtx<F>` argument, which isn't part of `fn_sig`
+    pub ctx_index: Option<usize>,
 }
 
 impl DetourInfo {
+    /// Checks that the hook function can be detoured, combining every problem into one error
+    pub fn new(
+        mut hook_attr: HookAttributeArgs,
+        hook_sig: Signature,
+        self_ty: Option<Type>,
+        trait_path: Option<Path>,
+        declaration: Option<Declaration>,
+        attrs: &[Attribute],
+        detour_names: &str,
+    ) -> Result<Self, syn::Error> {
+        let mut errors = Errors::default();
+        let fn_name = hook_sig.ident.clone();
+        let ctx_index = errors.ok(ctx_arg_index(&hook_sig)).flatten();
+        // With a declaration, the detour is named after it, and its signature, ABI and symbol are used
+        let (fn_sig, symbol) = match declaration {
+            Some(declaration) => {
+                if self_ty.is_some() {
+                    errors.push(syn::Error::new(
+                        fn_name.span(),
+                        "hooks using an `extern` block declaration can't be methods",
+                    ));
+                }
+                if let Some(abi) = &hook_attr.abi {
+                    errors.push(syn::Error::new_spanned(
+                        abi,
+                        "the ABI is taken from the `extern` block declaring the hooked function",
+                    ));
+                }
+                hook_attr.unsafety = Some(Default::default());
+                hook_attr.abi = Some(declaration.abi);
+                let symbol = match declaration.link_name {
+                    Some(link_name) => HookArg::symbol(&link_name.value(), link_name.span()),
+                    None => {
+                        let name = declaration.sig.ident.unraw();
+                        HookArg::symbol(&name.to_string(), name.span())
+                    }
+                };
+                (declaration.sig, symbol)
+            }
+            None => {
+                let name = hook_sig.ident.unraw();
+                let symbol = HookArg::symbol(&name.to_string(), name.span());
+                let mut fn_sig = hook_sig.clone();
+                // The hooked function doesn't take the context
+                if let Some(ctx_index) = ctx_index {
+                    fn_sig.inputs = fn_sig
+                        .inputs
+                        .into_iter()
+                        .enumerate()
+                        .filter(|(index, _)| *index != ctx_index)
+                        .map(|(_, arg)| arg)
+                        .collect();
+                }
+                (fn_sig, symbol)
+            }
+        };
+        let name = fn_sig.ident.unraw();
+        let detour_name = hook_attr.detour_name.clone().unwrap_or_else(|| {
+            Ident::new(&detour_names.replace("{}", &name.to_string()), name.span())
+        });
+        let lookup = hook_attr.hook_info.take().unwrap_or(symbol);
+        if let Some(asyncness) = &hook_sig.asyncness {
+            errors.push(syn::Error::new(asyncness.span, "hooks can't be `async`"));
+        }
+        if let Some(constness) = &hook_sig.constness {
+            errors.push(syn::Error::new(constness.span, "hooks can't be `const`"));
+        }
+        if let Some(param) = hook_sig
+            .generics
+            .params
+            .iter()
+            .find(|param| !matches!(param, GenericParam::Lifetime(_)))
+        {
+            errors.push(syn::Error::new_spanned(
+                param,
+                "hooks can't have type or const parameters, only lifetimes",
+            ));
+        }
+        if let Some(variadic) = &fn_sig.variadic {
+            errors.push(syn::Error::new_spanned(
+                variadic,
+                "variadic functions can't be hooked",
+            ));
+        }
+        if let Some(abi) = &hook_attr.abi {
+            let abi_name = abi
+                .name
+                .as_ref()
+                .map_or_else(|| "C".to_string(), LitStr::value);
+            if !SUPPORTED_ABIS.contains(&abi_name.as_str()) {
+                errors.push(syn::Error::new_spanned(
+                    abi,
+                    format!(
+                        "unsupported ABI `{abi_name}`, expected one of {}",
+                        SUPPORTED_ABIS.join(", ")
+                    ),
+                ));
+            }
+        }
+        let arg_types = errors.ok(fn_types(&fn_sig, self_ty.as_ref()));
+        let output = errors.ok(fn_output(&fn_sig));
+        // Borrows are passed as raw pointers, which safe callers of the `StaticDetour` could make up
+        if hook_attr.unsafety.is_none() {
+            let borrowed_args = fn_sig
+                .inputs
+                .iter()
+                .zip(arg_types.iter().flatten())
+                .filter(|(_, (_, passing))| *passing != Passing::Value)
+                .map(|(arg, _)| match arg {
+                    FnArg::Receiver(receiver) => receiver.self_token.span,
+                    FnArg::Typed(arg) => arg.ty.span(),
+                });
+            let borrowed_output = match (&fn_sig.output, &output) {
+                (ReturnType::Type(_, ty), Some((_, passing))) if *passing != Passing::Value => {
+                    Some(ty.span())
+                }
+                _ => None,
+            };
+            for span in borrowed_args.chain(borrowed_output) {
+                errors.push(syn::Error::new(
+                    span,
+                    "hooks with borrowed arguments or return values must be `unsafe`, \
+                     the hooked function passes them as raw pointers",
+                ));
+            }
+        }
+        errors.into_result()?;
+
+        let (Some(arg_types), Some((output_type, output_passing))) = (arg_types, output) else {
+            unreachable!("errors were returned above");
+        };
+        Ok(Self {
+            hook_attr,
+            detour_name,
+            lookup,
+            fn_name,
+            fn_sig,
+            self_ty,
+            trait_path,
+            arg_types,
+            output_type,
+            output_passing,
+            cfg_attrs: cfg_attrs(attrs),
+            doc_attrs: doc_attrs(attrs),
+            ctx_index,
+        })
+    }
+
+    /// Path to the hook function, e.g. `newstate` or `<Player>::take_damage`
+    fn fn_path(&self) -> TokenStream {
+        let ident = &self.fn_name;
+        match (&self.self_ty, &self.trait_path) {
+            (Some(self_ty), Some(trait_path)) => {
+                quote::quote! { <#self_ty as #trait_path>::#ident }
+            }
+            (Some(self_ty), None) => quote::quote! { <#self_ty>::#ident },
+            _ => quote::quote! { #ident },
+        }
+    }
+
+    /// The hook function as passed to `StaticDetour::initialize`.
+    /// It's wrapped in a closure if its receiver, arguments or return value are borrows,
+    /// which are passed as pointers by the `StaticDetour`, see [`Passing`]
+    fn hook_fn(&self, descriptor: &TokenStream) -> TokenStream {
+        let fn_path = self.fn_path();
+        let arg_types = &self.arg_types;
+        let output_passing = self.output_passing;
+        let is_passed_as_is = arg_types
+            .iter()
+            .map(|(_, passing)| passing)
+            .chain([&output_passing])
+            .all(|passing| *passing == Passing::Value);
+        if is_passed_as_is && self.ctx_index.is_none() {
+            return fn_path;
+        }
+        let args = fn_arg_names(&self.fn_sig);
+        let mut call_args: Vec<_> = args
+            .iter()
+            .zip(arg_types)
+            .map(|(arg, (_, passing))| passing.arg_to_hook(arg.to_token_stream()))
+            .collect();
+        if let Some(ctx_index) = self.ctx_index {
+            let krate_name = crate_refs::parent_crate();
+            let detour_name = &self.detour_name;
+            call_args.insert(
+                ctx_index,
+                quote::quote! { &::#krate_name::HookCtx::__new(&#detour_name, &#descriptor) },
+            );
+        }
+        let ret = output_passing.ret_from_hook(quote::quote! { #fn_path(#(#call_args),*) });
+        quote::quote! {
+            |#(#args),*| #ret
+        }
+    }
+
     fn get_static_detour(&self) -> Item {
         let vis = self.hook_attr.vis.clone();
 
         let detour_krate = crate_refs::retour_crate();
-        let detour_name: &proc_macro2::Ident = &self.hook_attr.detour_name;
-        let fn_type_sig = fn_type(&self.fn_sig, &self.hook_attr);
+        let detour_name: &proc_macro2::Ident = &self.detour_name;
+        let fn_type_sig = fn_type(
+            &self.fn_sig,
+            &self.hook_attr,
+            &self.arg_types,
+            &self.output_type,
+        );
         let target_fn_decl = self.target_fn_decl();
-        let arg_names = fn_arg_names(&self.fn_sig).unwrap();
+        let arg_names = fn_arg_names(&self.fn_sig);
+
+        // Records where the hooked function was called from, before anything else runs
+        let enter_hook = self.ctx_index.map(|_| {
+            let krate_name = crate_refs::parent_crate();
+            quote::quote! { ::#krate_name::__private::enter_hook(); }
+        });
 
+        let cfg_attrs = &self.cfg_attrs;
+        let doc_attrs = &self.doc_attrs;
         Item::Verbatim(quote_spanned! {self.hook_attr.span()=>
+            #(#cfg_attrs)*
+            #(#doc_attrs)*
             #[allow(non_upper_case_globals)]
             #vis static #detour_name: ::#detour_krate::StaticDetour<#fn_type_sig> = {
                 #[inline(never)]
                 #[allow(unused_unsafe)]
                 #target_fn_decl {
+                    #enter_hook
                     #[allow(unused_unsafe)]
                     (#detour_name.__detour())(#(#arg_names),*)
                 }
@@ -87,55 +764,276 @@ impl DetourInfo {
         })
     }
 
+    fn cfg_tokens(&self) -> String {
+        self.cfg_attrs
+            .iter()
+            .map(|attr| attr.to_token_stream().to_string())
+            .collect()
+    }
+
+    /// With `check = path`, fails to compile unless `path` has exactly the detour's function type, i.e. they
+    /// have the same argument types, return type, ABI and unsafety.
+    /// A safe function still coerces to an `unsafe` function type, so for those it's also called in an
+    /// `unsafe` block that must be needed
+    /// ```ignore
+    /// const _: () = {
+    ///     let _: unsafe extern "C" fn(*mut lua_State, c_int) = crate::ffi::lua_settop;
+    ///     #[deny(unused_unsafe)]
+    ///     #[allow(unreachable_code)]
+    ///     let _ = || unsafe { crate::ffi::lua_settop(loop {}, loop {}) };
+    /// };
+    /// ```
+    fn generate_signature_check(&self) -> Option<Item> {
+        let path = &self.hook_attr.check.as_ref()?.path;
+        let fn_type_sig = fn_type(
+            &self.fn_sig,
+            &self.hook_attr,
+            &self.arg_types,
+            &self.output_type,
+        );
+        // The arguments diverge, so only the unsafety is checked here, not their types again
+        let unsafety_check = self.hook_attr.unsafety.map(|_| {
+            let args = self.arg_types.iter().map(|_| quote::quote! { loop {} });
+            quote_spanned! {path.span()=>
+                #[deny(unused_unsafe)]
+                #[allow(unreachable_code)]
+                let _ = || unsafe { #path(#(#args),*) };
+            }
+        });
+        let cfg_attrs = &self.cfg_attrs;
+        Some(Item::Verbatim(quote_spanned! {path.span()=>
+            #(#cfg_attrs)*
+            const _: () = {
+                let _: #fn_type_sig = #path;
+                #unsafety_check
+            };
+        }))
+    }
+
     fn target_fn_decl(&self) -> TokenStream {
-        let input_types = self.fn_sig.inputs.iter();
+        let arg_names = fn_arg_names(&self.fn_sig);
+        let arg_types = self.arg_types.iter().map(|(ty, _)| ty);
         // output includes the `->` in the return type
-        let output_type = &self.fn_sig.output;
+        let output_type = &self.output_type;
         let abi = &self.hook_attr.abi;
         let unsafety = &self.hook_attr.unsafety;
 
         quote::quote_spanned! {self.hook_attr.span()=>
-            #unsafety #abi fn __ffi_detour(#(#input_types),*) #output_type
+            #unsafety #abi fn __ffi_detour(#(#arg_names: #arg_types),*) #output_type
+        }
+    }
+
+    /// `HookDescriptor` for this hook's entry in the module's `HOOKS` table
+    fn generate_descriptor(&self, module_name: &LitStr) -> TokenStream {
+        let krate_name = crate_refs::parent_crate();
+        let detour_name = &self.detour_name;
+        let detour_name_str = detour_name.to_string();
+        let fn_name_str = match &self.self_ty {
+            Some(self_ty) => format!("{}::{}", self_ty.to_token_stream(), self.fn_name),
+            None => self.fn_name.to_string(),
+        };
+        let abi = match &self.hook_attr.abi {
+            Some(abi) => {
+                // `extern` without an ABI string is `extern "C"`
+                let abi_name = abi
+                    .name
+                    .as_ref()
+                    .map_or_else(|| "C".to_string(), |name| name.value());
+                quote::quote! { Some(#abi_name) }
+            }
+            None => quote::quote! { None },
+        };
+        let lookup_new_fn = self.lookup.get_lookup_data_new_fn(module_name);
+        let group = match &self.hook_attr.group {
+            Some(group) => {
+                let group = &group.value;
+                quote::quote! { Some(#group) }
+            }
+            None => quote::quote! { None },
+        };
+        let optional = self.hook_attr.optional.is_some();
+        quote::quote! {
+            ::#krate_name::HookDescriptor::__new(
+                #detour_name_str,
+                #fn_name_str,
+                #abi,
+                #lookup_new_fn,
+                #group,
+                #optional,
+                || #detour_name.is_enabled(),
+            )
         }
     }
 
-    fn generate_detour_init(&self, module_name: &LitStr) -> Item {
-        let lookup_new_fn = (self.hook_attr.hook_info).get_lookup_data_new_fn(module_name);
-        let detour_name = &self.hook_attr.detour_name;
-        let orig_func_name = &self.fn_sig.ident;
+    /// Generates the `StaticDetour` of a standalone `#[hook(module = "...")]`, and registers it in
+    /// `REGISTERED_HOOKS` for `init_all`
+    pub fn generate_standalone(&self, module_name: &LitStr) -> Vec<Item> {
+        let krate_name = crate_refs::parent_crate();
+        let cfg_attrs = &self.cfg_attrs;
+        let descriptor = self.generate_descriptor(module_name);
+        let init = self.generate_detour_init(quote::quote! { DESCRIPTOR }, false);
+        let mut items = vec![self.get_static_detour()];
+        items.extend(self.generate_signature_check());
+        items.push(Item::Verbatim(quote::quote! {
+            #(#cfg_attrs)*
+            const _: () = {
+                static DESCRIPTOR: ::#krate_name::HookDescriptor = #descriptor;
+
+                #[::#krate_name::__private::linkme::distributed_slice(::#krate_name::REGISTERED_HOOKS)]
+                #[linkme(crate = ::#krate_name::__private::linkme)]
+                static REGISTERED_HOOK: ::#krate_name::RegisteredHook = ::#krate_name::RegisteredHook::__new(
+                    &DESCRIPTOR,
+                    ::core::module_path!(),
+                    || unsafe { #init },
+                );
+            };
+        }));
+        items
+    }
+
+    /// Initializes the `StaticDetour` and enables it, reporting the outcome for `descriptor`
+    fn generate_detour_init(&self, descriptor: TokenStream, load_if_missing: bool) -> Item {
+        let load = if load_if_missing {
+            quote::format_ident!("LoadIfMissing")
+        } else {
+            quote::format_ident!("Attach")
+        };
+        let detour_name = &self.detour_name;
+        let hook_fn = self.hook_fn(&descriptor);
         let parent_krate = crate_refs::parent_crate();
         let detour_krate = crate_refs::retour_crate();
         Item::Verbatim(quote_spanned! {self.hook_attr.span()=>
-            ::#parent_krate::init_detour(
-                #lookup_new_fn,
+            ::#parent_krate::init_hook(
+                &#descriptor,
+                ::#parent_krate::ModuleLoad::#load,
                 |addr| {
                     #detour_name
-                        .initialize(::#detour_krate::Function::from_ptr(addr), #orig_func_name)?
+                        .initialize(::#detour_krate::Function::from_ptr(addr), #hook_fn)?
                         .enable()?;
                     Ok(())
                 }
-            )?
+            )
         })
     }
 }
 
-impl Fold for Detours {
-    fn fold_item_fn(&mut self, item_fn: syn::ItemFn) -> syn::ItemFn {
-        let mut attrs = Vec::new();
+impl Detours {
+    /// Removes the `#[hook]` attributes, collecting a detour for each
+    fn take_hooks(
+        &mut self,
+        attrs: Vec<Attribute>,
+        outer_attrs: &[Attribute],
+        fn_sig: &Signature,
+        self_ty: Option<&Type>,
+        trait_path: Option<&Path>,
+    ) -> Vec<Attribute> {
+        let mut kept_attrs = Vec::new();
+        // The `impl` block's `#[cfg]`s apply to its methods too
+        let hook_fn_attrs: Vec<Attribute> = cfg_attrs(outer_attrs)
+            .into_iter()
+            .chain(
+                attrs
+                    .iter()
+                    .filter(|attr| !attr.path().is_ident("hook"))
+                    .cloned(),
+            )
+            .collect();
 
-        for attr in item_fn.attrs {
+        for attr in attrs {
             if !attr.path().is_ident("hook") {
-                attrs.push(attr);
+                kept_attrs.push(attr);
                 continue;
             }
-            let Ok(hook_attrs) = attr.parse_args::<HookAttributeArgs>() else {
+            // A bare `#[hook]` derives everything from the hook function
+            let hook_attrs = match &attr.meta {
+                Meta::Path(_) => syn::parse2::<HookAttributeArgs>(TokenStream::new()),
+                _ => attr.parse_args::<HookAttributeArgs>(),
+            };
+            let Some(mut hook_attrs) = self.errors.ok(hook_attrs) else {
                 continue;
             };
-            self.detours.push(DetourInfo {
-                hook_attr: hook_attrs,
-                fn_sig: item_fn.sig.clone(),
-            })
+            // `#[hook(lua_settop)]` refers to `lua_settop` if it's declared in an `extern` block
+            let declaration = hook_attrs.detour_name.as_ref().and_then(|name| {
+                self.declarations
+                    .iter()
+                    .find(|declaration| declaration.sig.ident == *name)
+                    .cloned()
+            });
+            if declaration.is_some() {
+                hook_attrs.detour_name = None;
+            }
+            let fn_sig = match self_ty {
+                Some(self_ty) => ReplaceSelf(self_ty).fold_signature(fn_sig.clone()),
+                None => fn_sig.clone(),
+            };
+            let info = DetourInfo::new(
+                hook_attrs,
+                fn_sig,
+                self_ty.cloned(),
+                trait_path.cloned(),
+                declaration,
+                &hook_fn_attrs,
+                &self.detour_names,
+            );
+            if let Some(info) = self.errors.ok(info) {
+                self.detours.push(info);
+            }
+        }
+        kept_attrs
+    }
+}
+
+impl Fold for Detours {
+    /// Nested modules with hooks get their own generated items, see [`SubModule`]
+    fn fold_item_mod(&mut self, mut item_mod: ItemMod) -> ItemMod {
+        let Some((_, items)) = item_mod.content.as_mut() else {
+            return item_mod;
+        };
+        let mut submodule = self.submodule();
+        submodule.fold_items(items);
+        if let Err(e) = submodule.check() {
+            self.errors.push(e);
+            return item_mod;
+        }
+        if submodule.detours.is_empty() && submodule.submodules.is_empty() {
+            return item_mod;
         }
+        items.extend(submodule.generate_items());
+        self.submodules.push(SubModule {
+            ident: item_mod.ident.clone(),
+            cfg_attrs: cfg_attrs(&item_mod.attrs),
+            has_groups: submodule.has_groups(),
+        });
+        item_mod
+    }
+
+    fn fold_item_fn(&mut self, item_fn: syn::ItemFn) -> syn::ItemFn {
+        let attrs = self.take_hooks(item_fn.attrs, &[], &item_fn.sig, None, None);
         ItemFn { attrs, ..item_fn }
     }
+
+    fn fold_item_impl(&mut self, mut item_impl: ItemImpl) -> ItemImpl {
+        let has_hooks = item_impl.items.iter().any(|item| {
+            matches!(item, ImplItem::Fn(impl_fn) if impl_fn.attrs.iter().any(|attr| attr.path().is_ident("hook")))
+        });
+        if has_hooks && !item_impl.generics.params.is_empty() {
+            self.errors.push(syn::Error::new_spanned(
+                &item_impl.generics,
+                "hooks can't be in a generic `impl` block",
+            ));
+        }
+        let trait_path = item_impl.trait_.as_ref().map(|(_, path, _)| path.clone());
+        for item in &mut item_impl.items {
+            if let ImplItem::Fn(impl_fn) = item {
+                impl_fn.attrs = self.take_hooks(
+                    std::mem::take(&mut impl_fn.attrs),
+                    &item_impl.attrs,
+                    &impl_fn.sig,
+                    Some(&item_impl.self_ty),
+                    trait_path.as_ref(),
+                );
+            }
+        }
+        item_impl
+    }
 }
@@ -1,95 +1,326 @@
+use proc_macro2::{Span, TokenStream};
 use syn::{
-    punctuated::Punctuated, spanned::Spanned, BareFnArg, FnArg, Pat, Signature, Type, TypeBareFn,
+    fold::Fold, parse_quote, punctuated::Punctuated, spanned::Spanned, Attribute, BareFnArg, FnArg,
+    GenericArgument, Ident, Lifetime, Meta, PathArguments, Receiver, ReturnType, Signature, Token,
+    Type, TypeBareFn, TypePath, TypeReference,
 };
 
 use crate::parse::HookAttributeArgs;
 
-pub fn fn_type(fn_sig: &Signature, hook_info: &HookAttributeArgs) -> Type {
-    let mut args = Punctuated::new();
-    let mut errs: Option<syn::Error> = None;
-    for arg in &fn_sig.inputs {
-        match arg {
-            FnArg::Typed(arg) => args.push(BareFnArg {
-                attrs: arg.attrs.clone(),
-                name: None,
-                ty: *arg.ty.clone(),
-            }),
-            FnArg::Receiver(_) => {
-                let err = syn::Error::new(
-                    arg.span(),
-                    "`self` is not currently supported by this macro",
-                );
-                match &mut errs {
-                    Some(errs) => errs.combine(err),
-                    None => errs = Some(err),
-                }
+/// Combines every error found while expanding, so they're all reported at once
+#[derive(Default)]
+pub struct Errors(Option<syn::Error>);
+
+impl Errors {
+    pub fn push(&mut self, err: syn::Error) {
+        match &mut self.0 {
+            Some(errors) => errors.combine(err),
+            None => self.0 = Some(err),
+        }
+    }
+
+    /// The value of `result`, or `None` after pushing its error
+    pub fn ok<T>(&mut self, result: Result<T, syn::Error>) -> Option<T> {
+        result.map_err(|err| self.push(err)).ok()
+    }
+
+    pub fn into_result(self) -> Result<(), syn::Error> {
+        match self.0 {
+            Some(err) => Err(err),
+            None => Ok(()),
+        }
+    }
+}
+
+/// How an argument or return value of a hook is passed through its `StaticDetour`'s function type
+///
+/// retour can't detour higher-ranked function types like `for<'a> fn(&'a T)`, so references are
+/// passed as the raw pointers they are ABI compatible with, and turned back into references around
+/// the hook function
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Passing {
+    /// Passed as is
+    Value,
+    /// `&T` as `*const T`
+    Ref,
+    /// `&mut T` as `*mut T`
+    Mut,
+    /// `Option<&T>` as `*const T`
+    OptionRef,
+    /// `Option<&mut T>` as `*mut T`
+    OptionMut,
+}
+
+impl Passing {
+    /// Converts an argument of the `StaticDetour`'s function type to the hook's argument
+    pub fn arg_to_hook(self, arg: TokenStream) -> TokenStream {
+        match self {
+            Passing::Value => arg,
+            Passing::Ref => quote::quote! { unsafe { &*#arg } },
+            Passing::Mut => quote::quote! { unsafe { &mut *#arg } },
+            Passing::OptionRef => quote::quote! { unsafe { #arg.as_ref() } },
+            Passing::OptionMut => quote::quote! { unsafe { #arg.as_mut() } },
+        }
+    }
+
+    /// Converts the hook's return value to the return type of the `StaticDetour`'s function type
+    pub fn ret_from_hook(self, ret: TokenStream) -> TokenStream {
+        match self {
+            Passing::Value => ret,
+            Passing::Ref => quote::quote! { ::core::ptr::from_ref(#ret) },
+            Passing::Mut => quote::quote! { ::core::ptr::from_mut(#ret) },
+            Passing::OptionRef => {
+                quote::quote! { #ret.map_or(::core::ptr::null(), ::core::ptr::from_ref) }
+            }
+            Passing::OptionMut => {
+                quote::quote! { #ret.map_or(::core::ptr::null_mut(), ::core::ptr::from_mut) }
             }
         }
     }
+}
+
+/// Function type of the `StaticDetour`, from the types returned by [`fn_types`] and [`fn_output`]
+pub fn fn_type(
+    fn_sig: &Signature,
+    hook_info: &HookAttributeArgs,
+    arg_types: &[(Type, Passing)],
+    output: &ReturnType,
+) -> Type {
+    let args = arg_types
+        .iter()
+        .map(|(ty, _)| BareFnArg {
+            attrs: Vec::new(),
+            name: None,
+            ty: ty.clone(),
+        })
+        .collect();
 
     Type::BareFn(TypeBareFn {
-        lifetimes: None, // TODO: maybe support lifetimes
+        // Borrows are passed as pointers, so the type is never higher-ranked
+        lifetimes: None,
         unsafety: hook_info.unsafety,
         abi: hook_info.abi.clone(),
         fn_token: fn_sig.fn_token,
         paren_token: fn_sig.paren_token,
         inputs: args,
-        variadic: fn_sig.variadic.clone().map(|var| syn::BareVariadic {
-            attrs: var.attrs,
-            name: None,
-            dots: var.dots,
-            comma: var.comma,
-        }),
-        output: fn_sig.output.clone(),
+        variadic: None,
+        output: output.clone(),
     })
 }
 
-pub fn fn_arg_names(fn_sig: &Signature) -> Result<Vec<&Pat>, syn::Error> {
-    let mut args = Vec::new();
-    let mut errs: Option<syn::Error> = None;
-    for arg in &fn_sig.inputs {
-        match arg {
-            FnArg::Typed(arg) => args.push(arg.pat.as_ref()),
-            FnArg::Receiver(_) => {
-                let err = syn::Error::new(
-                    arg.span(),
-                    "`self` is not currently supported by this macro",
-                );
-                match &mut errs {
-                    Some(errs) => errs.combine(err),
-                    None => errs = Some(err),
-                }
+/// `#[cfg]`s of a hook function, to put on everything generated for it. `#[cfg_attr]`s are included
+/// when they only expand to `#[cfg]`s, anything else could only apply to the function itself
+pub fn cfg_attrs(attrs: &[Attribute]) -> Vec<Attribute> {
+    attrs
+        .iter()
+        .filter(|attr| {
+            if attr.path().is_ident("cfg") {
+                return true;
             }
+            if !attr.path().is_ident("cfg_attr") {
+                return false;
+            }
+            let Ok(metas) = attr.parse_args_with(Punctuated::<Meta, Token![,]>::parse_terminated)
+            else {
+                return false;
+            };
+            // The first one is the condition
+            metas.len() > 1 && metas.iter().skip(1).all(|meta| meta.path().is_ident("cfg"))
+        })
+        .cloned()
+        .collect()
+}
+
+/// Doc comments of a hook function, to document its `StaticDetour` with
+pub fn doc_attrs(attrs: &[Attribute]) -> Vec<Attribute> {
+    attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("doc"))
+        .cloned()
+        .collect()
+}
+
+/// Position of the hook function's `ctx: &HookCtx<F>` argument, if it takes one.
+/// It must be the first argument, or the first after the receiver
+pub fn ctx_arg_index(fn_sig: &Signature) -> Result<Option<usize>, syn::Error> {
+    let first_index = usize::from(matches!(fn_sig.inputs.first(), Some(FnArg::Receiver(_))));
+    let mut ctx_index = None;
+    for (index, arg) in fn_sig.inputs.iter().enumerate() {
+        let FnArg::Typed(arg) = arg else {
+            continue;
+        };
+        if !is_hook_ctx(&arg.ty) {
+            continue;
         }
+        if index != first_index {
+            return Err(syn::Error::new_spanned(
+                &arg.ty,
+                "`HookCtx` must be the first argument, or the first after `self`",
+            ));
+        }
+        ctx_index = Some(index);
     }
-    if let Some(e) = errs {
-        Err(e)
-    } else {
-        Ok(args)
-    }
+    Ok(ctx_index)
+}
+
+/// `&HookCtx<F>`, with any path to `HookCtx`
+fn is_hook_ctx(ty: &Type) -> bool {
+    let Type::Reference(reference) = ty else {
+        return false;
+    };
+    let Type::Path(path) = &*reference.elem else {
+        return false;
+    };
+    path.path
+        .segments
+        .last()
+        .is_some_and(|segment| segment.ident == "HookCtx")
+}
+
+/// Identifiers the generated code forwards the arguments with, one per argument including the receiver.
+/// The hook's own patterns (`_`, `mut x`, `Point { x, y }`...) are left to the hook function
+pub fn fn_arg_names(fn_sig: &Signature) -> Vec<Ident> {
+    fn_sig
+        .inputs
+        .iter()
+        .enumerate()
+        .map(|(i, arg)| Ident::new(&format!("__arg{i}"), arg.span()))
+        .collect()
 }
 
-pub fn fn_types(fn_sig: &Signature) -> Result<Vec<&Type>, syn::Error> {
+/// Types of the arguments in the `StaticDetour`'s function type, and how each is passed to the hook.
+/// A method's receiver is a pointer to `self_ty`
+pub fn fn_types(
+    fn_sig: &Signature,
+    self_ty: Option<&Type>,
+) -> Result<Vec<(Type, Passing)>, syn::Error> {
     let mut types = Vec::new();
-    let mut errs: Option<syn::Error> = None;
+    let mut errors = Errors::default();
     for arg in &fn_sig.inputs {
-        match arg {
-            FnArg::Typed(arg) => types.push(arg.ty.as_ref()),
-            FnArg::Receiver(_) => {
-                let err = syn::Error::new(
-                    arg.span(),
-                    "`self` is not currently supported by this macro",
-                );
-                match &mut errs {
-                    Some(errs) => errs.combine(err),
-                    None => errs = Some(err),
-                }
-            }
+        let ty = match arg {
+            FnArg::Typed(arg) => ffi_type(&arg.ty),
+            FnArg::Receiver(receiver) => receiver_type(receiver, self_ty),
+        };
+        types.extend(errors.ok(ty));
+    }
+    errors.into_result()?;
+    Ok(types)
+}
+
+/// Return type in the `StaticDetour`'s function type, and how the hook's return value is converted to it
+pub fn fn_output(fn_sig: &Signature) -> Result<(ReturnType, Passing), syn::Error> {
+    match &fn_sig.output {
+        ReturnType::Default => Ok((ReturnType::Default, Passing::Value)),
+        ReturnType::Type(arrow, ty) => {
+            let (ty, passing) = ffi_type(ty)?;
+            Ok((ReturnType::Type(*arrow, Box::new(ty)), passing))
         }
     }
-    if let Some(e) = errs {
-        Err(e)
-    } else {
-        Ok(types)
+}
+
+/// `&self` is passed as `*const Self` and `&mut self` as `*mut Self`, i.e. the `this` pointer
+fn receiver_type(
+    receiver: &Receiver,
+    self_ty: Option<&Type>,
+) -> Result<(Type, Passing), syn::Error> {
+    let Some(self_ty) = self_ty else {
+        return Err(syn::Error::new(
+            receiver.self_token.span,
+            "`self` is only supported on hooks inside an `impl` block",
+        ));
+    };
+    if receiver.reference.is_none() || receiver.colon_token.is_some() {
+        return Err(syn::Error::new(
+            receiver.self_token.span,
+            "only `&self` and `&mut self` receivers are supported, they are passed as the `this` pointer",
+        ));
+    }
+    Ok(match receiver.mutability {
+        Some(_) => (parse_quote!(*mut #self_ty), Passing::Mut),
+        None => (parse_quote!(*const #self_ty), Passing::Ref),
+    })
+}
+
+fn ffi_type(ty: &Type) -> Result<(Type, Passing), syn::Error> {
+    let (ffi_ty, passing) = match ty {
+        Type::Reference(reference) => pointer_to(reference, Passing::Ref, Passing::Mut),
+        Type::Path(path) => match option_reference(path) {
+            Some(reference) => pointer_to(reference, Passing::OptionRef, Passing::OptionMut),
+            None => (ty.clone(), Passing::Value),
+        },
+        ty => (ty.clone(), Passing::Value),
+    };
+    let mut find_lifetime = FindLifetime(None);
+    find_lifetime.fold_type(ffi_ty.clone());
+    if let Some(span) = find_lifetime.0 {
+        return Err(syn::Error::new(
+            span,
+            "hooks can only borrow through `&T`, `&mut T`, `Option<&T>` or `Option<&mut T>`, \
+             use a raw pointer or `'static` instead",
+        ));
+    }
+    Ok((ffi_ty, passing))
+}
+
+fn pointer_to(reference: &TypeReference, shared: Passing, exclusive: Passing) -> (Type, Passing) {
+    let elem = &reference.elem;
+    match reference.mutability {
+        Some(_) => (parse_quote!(*mut #elem), exclusive),
+        None => (parse_quote!(*const #elem), shared),
+    }
+}
+
+/// The `&T` in an `Option<&T>`
+fn option_reference(path: &TypePath) -> Option<&TypeReference> {
+    let segment = path.path.segments.last()?;
+    if path.qself.is_some() || segment.ident != "Option" {
+        return None;
+    }
+    let PathArguments::AngleBracketed(args) = &segment.arguments else {
+        return None;
+    };
+    match args.args.first()? {
+        GenericArgument::Type(Type::Reference(reference)) if args.args.len() == 1 => {
+            Some(reference)
+        }
+        _ => None,
+    }
+}
+
+/// Finds a borrow that isn't `'static`, without looking into function pointer types,
+/// which bind their own lifetimes
+struct FindLifetime(Option<Span>);
+
+impl Fold for FindLifetime {
+    fn fold_type_bare_fn(&mut self, bare_fn: TypeBareFn) -> TypeBareFn {
+        bare_fn
+    }
+
+    fn fold_type_reference(&mut self, reference: TypeReference) -> TypeReference {
+        if reference.lifetime.is_none() {
+            self.0.get_or_insert(reference.and_token.span);
+        }
+        syn::fold::fold_type_reference(self, reference)
+    }
+
+    fn fold_lifetime(&mut self, lifetime: Lifetime) -> Lifetime {
+        if lifetime.ident != "static" {
+            self.0.get_or_insert(lifetime.span());
+        }
+        lifetime
+    }
+}
+
+/// Replaces `Self` with the type of the `impl` block, so a method's signature can be used outside of it
+pub struct ReplaceSelf<'a>(pub &'a Type);
+
+impl Fold for ReplaceSelf<'_> {
+    fn fold_type(&mut self, ty: Type) -> Type {
+        match ty {
+            Type::Path(path) if path.qself.is_none() && path.path.is_ident("Self") => {
+                self.0.clone()
+            }
+            ty => syn::fold::fold_type(self, ty),
+        }
     }
 }
@@ -4,14 +4,24 @@ mod fold;
 mod helpers;
 mod parse;
 
+use parse::{HookModuleArgs, StandaloneHookArgs};
 use proc_macro::TokenStream;
-use syn::{parse_macro_input, ItemMod, LitStr};
+use syn::{parse_macro_input, ItemFn, ItemMod};
 
 #[proc_macro_attribute]
 pub fn hook_module(args: TokenStream, input: TokenStream) -> TokenStream {
     let ast = parse_macro_input!(input as ItemMod);
-    let args = parse_macro_input!(args as LitStr);
+    let args = parse_macro_input!(args as HookModuleArgs);
 
     let stream = expand::expand(ast, args).unwrap_or_else(syn::Error::into_compile_error);
     stream.into()
 }
+
+#[proc_macro_attribute]
+pub fn hook(args: TokenStream, input: TokenStream) -> TokenStream {
+    let ast = parse_macro_input!(input as ItemFn);
+    let args = parse_macro_input!(args as StandaloneHookArgs);
+
+    let stream = expand::expand_hook(ast, args).unwrap_or_else(syn::Error::into_compile_error);
+    stream.into()
+}
@@ -1,6 +1,9 @@
-use proc_macro2::TokenStream;
+use proc_macro2::{Span, TokenStream};
 use quote::ToTokens;
-use syn::{parse::Parse, token::Unsafe, Abi, Ident, LitInt, LitStr, Token, Visibility};
+use syn::{
+    parenthesized, parse::Parse, token::Paren, token::Unsafe, Abi, Ident, LitInt, LitStr, Path,
+    Token, Visibility,
+};
 
 use crate::crate_refs::parent_crate;
 
@@ -8,35 +11,301 @@ pub mod kw {
     syn::custom_keyword!(hook);
     syn::custom_keyword!(offset);
     syn::custom_keyword!(symbol);
+    syn::custom_keyword!(pattern);
+    syn::custom_keyword!(follow);
+    syn::custom_keyword!(rel32);
+    syn::custom_keyword!(load_if_missing);
+    syn::custom_keyword!(deferred);
+    syn::custom_keyword!(transactional);
+    syn::custom_keyword!(optional);
+    syn::custom_keyword!(group);
+    syn::custom_keyword!(detour_names);
+    syn::custom_keyword!(check);
+    syn::custom_keyword!(module);
+}
+
+/// Arguments of `#[hook_module("lua52.dll", ...)]`
+pub struct HookModuleArgs {
+    pub module_name: LitStr,
+    /// Load the module if it isn't already loaded, instead of failing with `ModuleNotLoaded`
+    pub load_if_missing: Option<kw::load_if_missing>,
+    /// Install hooks once the module gets loaded, instead of failing with `ModuleNotLoaded`
+    pub deferred: Option<kw::deferred>,
+    /// Disable every installed hook again if any hook of the module fails
+    pub transactional: Option<kw::transactional>,
+    /// `detour_names = "{}_Detour"`, naming scheme for hooks without a detour name
+    pub detour_names: Option<DetourNamesArg>,
+}
+
+impl Parse for HookModuleArgs {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        let mut args = Self {
+            module_name: input.parse()?,
+            load_if_missing: None,
+            deferred: None,
+            transactional: None,
+            detour_names: None,
+        };
+        while !input.is_empty() {
+            input.parse::<Token![,]>()?;
+            if input.is_empty() {
+                break;
+            }
+            if input.peek(kw::detour_names) {
+                set_once(&mut args.detour_names, input.parse()?)?;
+                continue;
+            }
+            let option: Ident = input.parse()?;
+            let span = option.span();
+            match option.to_string().as_str() {
+                "load_if_missing" => {
+                    set_once(&mut args.load_if_missing, kw::load_if_missing(span))?
+                }
+                "deferred" => set_once(&mut args.deferred, kw::deferred(span))?,
+                "transactional" => set_once(&mut args.transactional, kw::transactional(span))?,
+                _ => {
+                    return Err(syn::Error::new(
+                        span,
+                        format!("unknown `hook_module` option `{option}`"),
+                    ))
+                }
+            }
+        }
+        if let (Some(_), Some(deferred)) = (&args.load_if_missing, &args.deferred) {
+            return Err(syn::Error::new(
+                deferred.span,
+                "`deferred` and `load_if_missing` can't be used together",
+            ));
+        }
+        Ok(args)
+    }
+}
+
+/// `detour_names = "{}_Detour"` in a `#[hook_module(...)]`, where `{}` is replaced by the hook function's name
+pub struct DetourNamesArg {
+    pub detour_names_token: kw::detour_names,
+    pub eq: Token![=],
+    pub value: LitStr,
+}
+
+impl DetourNamesArg {
+    pub const DEFAULT: &'static str = "{}_Detour";
+}
+
+impl Parse for DetourNamesArg {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        let detour_names_token = input.parse()?;
+        let eq = input.parse()?;
+        let value: LitStr = input.parse()?;
+        let scheme = value.value();
+        if scheme.matches("{}").count() != 1
+            || syn::parse_str::<Ident>(&scheme.replace("{}", "hook")).is_err()
+        {
+            return Err(syn::Error::new(
+                value.span(),
+                "`detour_names` must be an identifier containing `{}` once, like \"{}_Detour\"",
+            ));
+        }
+        Ok(Self {
+            detour_names_token,
+            eq,
+            value,
+        })
+    }
+}
+
+impl ToTokens for DetourNamesArg {
+    fn to_tokens(&self, tokens: &mut proc_macro2::TokenStream) {
+        self.detour_names_token.to_tokens(tokens);
+        self.eq.to_tokens(tokens);
+        self.value.to_tokens(tokens);
+    }
+}
+
+/// Store an option, erroring if it was already given
+fn set_once<T: ToTokens>(slot: &mut Option<T>, value: T) -> syn::Result<()> {
+    if slot.is_some() {
+        return Err(syn::Error::new_spanned(
+            &value,
+            format!("duplicate `{}`", value.to_token_stream()),
+        ));
+    }
+    *slot = Some(value);
+    Ok(())
 }
 
 pub struct HookAttributeArgs {
     pub vis: Visibility,
     pub unsafety: Option<Unsafe>,
     pub abi: Option<Abi>,
-    pub detour_name: Ident,
-    pub comma: Token![,],
-    pub hook_info: HookArg,
+    /// Derived from the hook function's name and the module's `detour_names` if missing
+    pub detour_name: Option<Ident>,
+    /// Looks up the symbol with the same name as the hook function if missing
+    pub hook_info: Option<HookArg>,
+    /// Skip the hook instead of failing `init_detours` if its lookup can't be resolved
+    pub optional: Option<kw::optional>,
+    /// `group = "render"`, so the hook can be handled together with the rest of its group
+    pub group: Option<GroupArg>,
+    /// `check = crate::ffi::lua_newstate`, asserts at compile time that the detour's function type is the
+    /// referenced function's
+    pub check: Option<CheckArg>,
 }
 
 impl Parse for HookAttributeArgs {
     fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
-        Ok(Self {
+        let mut args = Self {
             vis: input.parse()?,
             unsafety: input.parse()?,
             abi: input.parse()?,
-            detour_name: input.parse()?,
-            comma: input.parse()?,
-            hook_info: input.parse()?,
-        })
+            detour_name: None,
+            hook_info: None,
+            optional: None,
+            group: None,
+            check: None,
+        };
+        // `symbol = "..."` or `optional` without a detour name before it
+        if input.peek(Ident) && !input.peek2(Token![=]) && !peek_flag(input) {
+            args.detour_name = Some(input.parse()?);
+        }
+        let has_head = !matches!(args.vis, Visibility::Inherited)
+            || args.unsafety.is_some()
+            || args.abi.is_some()
+            || args.detour_name.is_some();
+
+        let mut needs_comma = has_head;
+        while !input.is_empty() {
+            if needs_comma {
+                input.parse::<Token![,]>()?;
+                if input.is_empty() {
+                    break;
+                }
+            }
+            needs_comma = true;
+            let option: Ident = input.fork().parse()?;
+            match option.to_string().as_str() {
+                "offset" | "symbol" | "pattern" => set_once(&mut args.hook_info, input.parse()?)?,
+                "optional" => set_once(&mut args.optional, input.parse()?)?,
+                "group" => set_once(&mut args.group, input.parse()?)?,
+                "check" => set_once(&mut args.check, input.parse()?)?,
+                _ => {
+                    return Err(syn::Error::new(
+                        option.span(),
+                        format!("unknown `hook` option `{option}`"),
+                    ))
+                }
+            }
+        }
+        Ok(args)
     }
 }
 
+/// Whether the next option is written without a value, so it isn't mistaken for a detour name
+fn peek_flag(input: syn::parse::ParseStream) -> bool {
+    input.peek(kw::optional)
+}
+
 impl ToTokens for HookAttributeArgs {
     fn to_tokens(&self, tokens: &mut proc_macro2::TokenStream) {
+        self.vis.to_tokens(tokens);
+        self.unsafety.to_tokens(tokens);
+        self.abi.to_tokens(tokens);
         self.detour_name.to_tokens(tokens);
-        self.comma.to_tokens(tokens);
-        self.hook_info.to_tokens(tokens);
+        let options = [
+            self.hook_info.as_ref().map(ToTokens::to_token_stream),
+            self.optional.as_ref().map(ToTokens::to_token_stream),
+            self.group.as_ref().map(ToTokens::to_token_stream),
+            self.check.as_ref().map(ToTokens::to_token_stream),
+        ];
+        for option in options.into_iter().flatten() {
+            <Token![,]>::default().to_tokens(tokens);
+            option.to_tokens(tokens);
+        }
+    }
+}
+
+/// Arguments of a standalone `#[hook(module = "lua52.dll", ...)]`, outside of a `#[hook_module]`
+pub struct StandaloneHookArgs {
+    pub module_name: LitStr,
+    pub hook_attr: HookAttributeArgs,
+}
+
+impl Parse for StandaloneHookArgs {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        if !input.peek(kw::module) {
+            return Err(syn::Error::new(
+                input.span(),
+                "`#[hook]` outside of a `#[hook_module]` needs the module first, as in `#[hook(module = \"lua52.dll\", ...)]`",
+            ));
+        }
+        input.parse::<kw::module>()?;
+        input.parse::<Token![=]>()?;
+        let module_name = input.parse()?;
+        if !input.is_empty() {
+            input.parse::<Token![,]>()?;
+        }
+        Ok(Self {
+            module_name,
+            hook_attr: input.parse()?,
+        })
+    }
+}
+
+/// `group = "render"` in a `#[hook(...)]`
+pub struct GroupArg {
+    pub group_token: kw::group,
+    pub eq: Token![=],
+    pub value: LitStr,
+}
+
+impl Parse for GroupArg {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        let group_token = input.parse()?;
+        let eq = input.parse()?;
+        let value: LitStr = input.parse()?;
+        if value.value().is_empty() {
+            return Err(syn::Error::new(
+                value.span(),
+                "group name must not be empty",
+            ));
+        }
+        Ok(Self {
+            group_token,
+            eq,
+            value,
+        })
+    }
+}
+
+impl ToTokens for GroupArg {
+    fn to_tokens(&self, tokens: &mut proc_macro2::TokenStream) {
+        self.group_token.to_tokens(tokens);
+        self.eq.to_tokens(tokens);
+        self.value.to_tokens(tokens);
+    }
+}
+
+/// `check = crate::ffi::lua_newstate` in a `#[hook(...)]`
+pub struct CheckArg {
+    pub check_token: kw::check,
+    pub eq: Token![=],
+    pub path: Path,
+}
+
+impl Parse for CheckArg {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        Ok(Self {
+            check_token: input.parse()?,
+            eq: input.parse()?,
+            path: input.parse()?,
+        })
+    }
+}
+
+impl ToTokens for CheckArg {
+    fn to_tokens(&self, tokens: &mut proc_macro2::TokenStream) {
+        self.check_token.to_tokens(tokens);
+        self.eq.to_tokens(tokens);
+        self.path.to_tokens(tokens);
     }
 }
 
@@ -51,9 +320,106 @@ pub enum HookArg {
         eq: Token![=],
         value: LitStr,
     },
+    Pattern {
+        pattern_token: kw::pattern,
+        eq: Token![=],
+        value: LitStr,
+        follow: Option<FollowArg>,
+    },
+}
+
+/// `, follow = rel32(1)` following a `pattern = "..."`
+pub struct FollowArg {
+    pub comma: Token![,],
+    pub follow_token: kw::follow,
+    pub eq: Token![=],
+    pub rel32_token: kw::rel32,
+    pub paren: Paren,
+    pub position: LitInt,
+}
+
+impl FollowArg {
+    fn peek(input: syn::parse::ParseStream) -> bool {
+        input.peek(Token![,]) && input.peek2(kw::follow)
+    }
+
+    /// Checks the displacement read after a match is part of `pattern`
+    fn validate(&self, pattern: &LitStr) -> syn::Result<()> {
+        let position: usize = self.position.base10_parse()?;
+        let pattern_len = pattern.value().split_whitespace().count();
+        if position
+            .checked_add(4)
+            .is_some_and(|end| end <= pattern_len)
+        {
+            return Ok(());
+        }
+        Err(syn::Error::new(
+            self.position.span(),
+            format!(
+                "`rel32({position})` reads bytes {position}..{} but the pattern is only {pattern_len} bytes long",
+                position.saturating_add(4)
+            ),
+        ))
+    }
+}
+
+impl Parse for FollowArg {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        let content;
+        Ok(Self {
+            comma: input.parse()?,
+            follow_token: input.parse()?,
+            eq: input.parse()?,
+            rel32_token: input.parse()?,
+            paren: parenthesized!(content in input),
+            position: content.parse()?,
+        })
+    }
+}
+
+impl ToTokens for FollowArg {
+    fn to_tokens(&self, tokens: &mut proc_macro2::TokenStream) {
+        self.comma.to_tokens(tokens);
+        self.follow_token.to_tokens(tokens);
+        self.eq.to_tokens(tokens);
+        self.rel32_token.to_tokens(tokens);
+        self.paren
+            .surround(tokens, |tokens| self.position.to_tokens(tokens));
+    }
 }
 
 impl HookArg {
+    /// `symbol = "name"`
+    pub fn symbol(name: &str, span: Span) -> Self {
+        Self::Symbol {
+            symbol_token: kw::symbol(span),
+            eq: Token![=](span),
+            value: LitStr::new(name, span),
+        }
+    }
+
+    /// Normalized description of the hooked function, the same for two hooks targeting the same function
+    pub fn target(&self) -> String {
+        match self {
+            Self::Offset { value, .. } => format!("offset {}", value.base10_digits()),
+            Self::Symbol { value, .. } => format!("symbol {}", value.value()),
+            Self::Pattern { value, follow, .. } => {
+                let bytes: Vec<_> = value
+                    .value()
+                    .split_whitespace()
+                    .map(|byte| match byte {
+                        "?" => "??".to_string(),
+                        byte => byte.to_ascii_uppercase(),
+                    })
+                    .collect();
+                let follow = follow
+                    .as_ref()
+                    .map(|follow| follow.position.base10_digits().to_string());
+                format!("pattern {} follow {follow:?}", bytes.join(" "))
+            }
+        }
+    }
+
     pub fn get_lookup_data_new_fn(&self, module_name: &LitStr) -> TokenStream {
         let krate_name = parent_crate();
         match self {
@@ -67,10 +433,53 @@ impl HookArg {
                     ::#krate_name::LookupData::from_symbol(#module_name, #value)
                 }
             }
+            Self::Pattern {
+                value,
+                follow: None,
+                ..
+            } => {
+                quote::quote! {
+                    ::#krate_name::LookupData::from_pattern(#module_name, #value)
+                }
+            }
+            Self::Pattern {
+                value,
+                follow: Some(follow),
+                ..
+            } => {
+                let position = &follow.position;
+                quote::quote! {
+                    ::#krate_name::LookupData::from_pattern_follow(
+                        #module_name,
+                        #value,
+                        ::#krate_name::Follow::Rel32(#position),
+                    )
+                }
+            }
         }
     }
 }
 
+/// Checks pattern is made of space separated hex bytes or `??` wildcards, like `"48 8B ?? 57"`
+fn validate_pattern(pattern: &LitStr) -> syn::Result<()> {
+    let value = pattern.value();
+    let mut bytes = value.split_whitespace().peekable();
+    if bytes.peek().is_none() {
+        return Err(syn::Error::new(pattern.span(), "pattern must not be empty"));
+    }
+    for byte in bytes {
+        let is_wildcard = byte == "?" || byte == "??";
+        let is_hex = byte.len() == 2 && byte.chars().all(|c| c.is_ascii_hexdigit());
+        if !is_wildcard && !is_hex {
+            return Err(syn::Error::new(
+                pattern.span(),
+                format!("invalid pattern byte `{byte}`, expected two hex digits or `??`"),
+            ));
+        }
+    }
+    Ok(())
+}
+
 impl Parse for HookArg {
     fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
         let lookahead = input.lookahead1();
@@ -86,6 +495,24 @@ impl Parse for HookArg {
                 eq: input.parse()?,
                 value: input.parse()?,
             })
+        } else if lookahead.peek(kw::pattern) {
+            let pattern_token = input.parse::<kw::pattern>()?;
+            let eq = input.parse()?;
+            let value = input.parse()?;
+            validate_pattern(&value)?;
+            let follow = if FollowArg::peek(input) {
+                let follow: FollowArg = input.parse()?;
+                follow.validate(&value)?;
+                Some(follow)
+            } else {
+                None
+            };
+            Ok(Self::Pattern {
+                pattern_token,
+                eq,
+                value,
+                follow,
+            })
         } else {
             Err(lookahead.error())
         }
@@ -113,6 +540,17 @@ impl ToTokens for HookArg {
                 eq.to_tokens(tokens);
                 value.to_tokens(tokens);
             }
+            HookArg::Pattern {
+                pattern_token,
+                eq,
+                value,
+                follow,
+            } => {
+                pattern_token.to_tokens(tokens);
+                eq.to_tokens(tokens);
+                value.to_tokens(tokens);
+                follow.to_tokens(tokens);
+            }
         }
     }
 }
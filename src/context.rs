@@ -0,0 +1,81 @@
+use std::{cell::Cell, ops::Deref};
+
+use retour::{Function, StaticDetour};
+
+use crate::HookDescriptor;
+
+extern "C" {
+    #[link_name = "llvm.returnaddress"]
+    fn llvm_return_address(level: i32) -> *const u8;
+}
+
+thread_local! {
+    /// Return address of the innermost hooked call on this thread, until its `HookCtx` is made
+    static RETURN_ADDRESS: Cell<usize> = const { Cell::new(0) };
+}
+
+/// Records the return address of the function it's inlined into, which is the generated function the
+/// hooked function jumps to, so it's the address the hooked function was called from
+#[doc(hidden)]
+#[inline(always)]
+pub fn enter_hook() {
+    let return_address = unsafe { llvm_return_address(0) } as usize;
+    RETURN_ADDRESS.with(|cell| cell.set(return_address));
+}
+
+/// Taken by hooks with a leading `ctx: &HookCtx<F>` argument, `F` being the `StaticDetour`'s function type
+///
+/// Dereferences to the hook's `StaticDetour`, so the original function can be called with `ctx.call(..)`
+/// ```ignore
+/// #[hook(unsafe extern "C" Lua_settop, symbol = "lua_settop")]
+/// fn settop(ctx: &HookCtx<unsafe extern "C" fn(*mut lua_State, c_int)>, l: *mut lua_State, idx: c_int) {
+///     println!("{} called from {:#x}", ctx.hook().detour_name, ctx.return_address());
+///     unsafe { ctx.call(l, idx) }
+/// }
+/// ```
+pub struct HookCtx<F: Function> {
+    detour: &'static StaticDetour<F>,
+    hook: &'static HookDescriptor,
+    return_address: usize,
+    call_number: usize,
+}
+
+impl<F: Function> HookCtx<F> {
+    #[doc(hidden)]
+    pub fn __new(detour: &'static StaticDetour<F>, hook: &'static HookDescriptor) -> Self {
+        Self {
+            detour,
+            hook,
+            return_address: RETURN_ADDRESS.with(Cell::get),
+            call_number: hook.count_call(),
+        }
+    }
+
+    /// The hook's `StaticDetour`, to call the original function through
+    pub fn detour(&self) -> &'static StaticDetour<F> {
+        self.detour
+    }
+
+    /// The hook's entry in the `HOOKS` table, with its name, lookup and resolved address
+    pub fn hook(&self) -> &'static HookDescriptor {
+        self.hook
+    }
+
+    /// Address the hooked function was called from
+    pub fn return_address(&self) -> usize {
+        self.return_address
+    }
+
+    /// How many times the hook was called, including this call
+    pub fn call_number(&self) -> usize {
+        self.call_number
+    }
+}
+
+impl<F: Function> Deref for HookCtx<F> {
+    type Target = StaticDetour<F>;
+
+    fn deref(&self) -> &Self::Target {
+        self.detour
+    }
+}
@@ -0,0 +1,142 @@
+use std::{path::Path, sync::Mutex, thread, time::Duration};
+
+use crate::{module, InitReport, Result};
+
+/// How often the loaded modules are checked for modules with pending hooks
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+type InitDetoursFn = unsafe fn() -> Result<InitReport>;
+type DeferredCallback = fn(&'static str, &Result<InitReport>);
+
+/// State of the hooks of a `#[hook_module("...", deferred)]`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DeferredStatus {
+    /// Waiting for the module to be loaded
+    Pending,
+    /// Module was loaded and its hooks were installed
+    Installed,
+    /// Module was loaded, but installing its hooks failed
+    Failed,
+}
+
+struct Deferred {
+    module: &'static str,
+    init_detours_fn: InitDetoursFn,
+    status: DeferredStatus,
+}
+
+struct State {
+    deferred: Vec<Deferred>,
+    callback: Option<DeferredCallback>,
+    watching: bool,
+}
+
+static STATE: Mutex<State> = Mutex::new(State {
+    deferred: Vec::new(),
+    callback: None,
+    watching: false,
+});
+
+/// Run `init_detours_fn` once `module` is loaded in the process
+///
+/// If the module is already loaded, hooks are installed right away and the result is returned.
+/// Otherwise an empty report is returned, and the hooks are installed by a background thread
+/// polling the loaded modules,
+/// so the module may run briefly before its hooks go live.
+/// Use [`deferred_status`] or [`on_deferred_install`] to know when that happens.
+///
+/// This is called by `init_detours` for a `#[hook_module("...", deferred)]`
+///
+/// # Safety
+/// Same as `init_detours_fn`, which may be called from another thread
+pub unsafe fn defer_detours(
+    module: &'static str,
+    init_detours_fn: InitDetoursFn,
+) -> Result<InitReport> {
+    if module::loaded_library(Path::new(module)).is_some() {
+        let result = init_detours_fn();
+        push_deferred(module, init_detours_fn, status_of(&result));
+        return result;
+    }
+
+    if !push_deferred(module, init_detours_fn, DeferredStatus::Pending) {
+        thread::spawn(watch_modules);
+    }
+    Ok(InitReport::default())
+}
+
+/// Status of the deferred hooks for `module`, or `None` if none were registered
+pub fn deferred_status(module: &str) -> Option<DeferredStatus> {
+    let state = STATE.lock().unwrap();
+    state
+        .deferred
+        .iter()
+        .rev()
+        .find(|deferred| deferred.module == module)
+        .map(|deferred| deferred.status)
+}
+
+/// Set a callback that is called whenever deferred hooks are installed after their module loads
+pub fn on_deferred_install(callback: fn(&'static str, &Result<InitReport>)) {
+    STATE.lock().unwrap().callback = Some(callback);
+}
+
+fn status_of(result: &Result<InitReport>) -> DeferredStatus {
+    match result {
+        Ok(_) => DeferredStatus::Installed,
+        Err(_) => DeferredStatus::Failed,
+    }
+}
+
+/// Returns whether the watcher thread was already running
+fn push_deferred(
+    module: &'static str,
+    init_detours_fn: InitDetoursFn,
+    status: DeferredStatus,
+) -> bool {
+    let mut state = STATE.lock().unwrap();
+    state.deferred.push(Deferred {
+        module,
+        init_detours_fn,
+        status,
+    });
+    let watching = state.watching;
+    state.watching |= status == DeferredStatus::Pending;
+    watching
+}
+
+fn watch_modules() {
+    loop {
+        thread::sleep(POLL_INTERVAL);
+
+        let (ready, callback) = {
+            let mut state = STATE.lock().unwrap();
+            let pending: Vec<_> = state
+                .deferred
+                .iter()
+                .enumerate()
+                .filter(|(_, deferred)| deferred.status == DeferredStatus::Pending)
+                .collect();
+            if pending.is_empty() {
+                state.watching = false;
+                return;
+            }
+            let ready: Vec<_> = pending
+                .into_iter()
+                .filter(|(_, deferred)| {
+                    module::loaded_library(Path::new(deferred.module)).is_some()
+                })
+                .map(|(i, deferred)| (i, deferred.module, deferred.init_detours_fn))
+                .collect();
+            (ready, state.callback)
+        };
+
+        for (i, module, init_detours_fn) in ready {
+            let result = unsafe { init_detours_fn() };
+            STATE.lock().unwrap().deferred[i].status = status_of(&result);
+            if let Some(callback) = callback {
+                callback(module, &result);
+            }
+        }
+    }
+}
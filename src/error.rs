@@ -1,11 +1,29 @@
 use std::fmt::Display;
 
+use crate::InitReport;
+
 #[derive(Debug)]
 pub enum Error {
     /// Detour error encountered within the [`retour`] crate
     DetourError(retour::Error),
     /// Module trying to be hooked is not currently loaded in memory
     ModuleNotLoaded,
+    /// Symbol was not exported by the module
+    SymbolNotFound,
+    /// Byte pattern could not be parsed, or its [`Follow`](crate::Follow) reads past the end of it,
+    /// see [`LookupData::Pattern`](crate::LookupData::Pattern)
+    InvalidPattern,
+    /// Byte pattern did not match anything in the module's executable sections
+    PatternNotFound,
+    /// Byte pattern matched more than one location in the module's executable sections
+    PatternNotUnique,
+    /// Hook was installed, then disabled again because another hook of its
+    /// `#[hook_module("...", transactional)]` failed
+    RolledBack,
+    /// No hook of the `#[hook_module]` has the `group = "..."` that was asked for
+    UnknownGroup,
+    /// One or more hooks of a `#[hook_module]` failed to initialize, the report has each hook's outcome
+    InitFailed(InitReport),
 }
 
 impl From<retour::Error> for Error {
@@ -18,7 +36,31 @@ impl Display for Error {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             Error::DetourError(e) => f.write_fmt(format_args!("Detour Error: {e:?}")),
-            Error::ModuleNotLoaded => f.write_str("Module trying to be hooked is not currently loaded in memory"),
+            Error::ModuleNotLoaded => {
+                f.write_str("Module trying to be hooked is not currently loaded in memory")
+            }
+            Error::SymbolNotFound => f.write_str("Symbol was not found in the module"),
+            Error::InvalidPattern => f.write_str("Byte pattern is not valid"),
+            Error::PatternNotFound => f.write_str("Byte pattern was not found in the module"),
+            Error::PatternNotUnique => {
+                f.write_str("Byte pattern matched more than one location in the module")
+            }
+            Error::RolledBack => {
+                f.write_str("Hook was disabled again because another hook in its module failed")
+            }
+            Error::UnknownGroup => f.write_str("No hook in the module belongs to this group"),
+            Error::InitFailed(report) => {
+                let failures = report.failures().count();
+                writeln!(
+                    f,
+                    "{failures} of {} hooks failed to initialize:",
+                    report.hooks.len()
+                )?;
+                for hook in report.failures() {
+                    writeln!(f, "  {hook}")?;
+                }
+                Ok(())
+            }
         }
     }
 }
@@ -0,0 +1,211 @@
+use std::any::TypeId;
+
+use retour::{Function, RawDetour};
+
+use crate::{Error, HookReport, InitReport, LookupData, ModuleLoad, Result};
+
+/// Hooks defined at runtime, e.g. from a config file or a plugin manifest, instead of with [`hook_module`](crate::hook_module)
+///
+/// ```ignore
+/// let mut hooks = HookSet::new().hook::<unsafe extern "C" fn(i32, i32) -> i32>(
+///     "add",
+///     LookupData::from_symbol("libfoo.so", "add"),
+///     my_add,
+/// );
+/// unsafe { hooks.init() }?;
+/// // Calls the original `add`
+/// let add = hooks.original::<unsafe extern "C" fn(i32, i32) -> i32>("add").unwrap();
+/// ```
+/// Names read at runtime can be made `'static` with [`String::leak`].
+/// The hooks are disabled and removed once the `HookSet` is dropped
+#[derive(Default)]
+pub struct HookSet {
+    load: ModuleLoad,
+    hooks: Vec<RuntimeHook>,
+}
+
+struct RuntimeHook {
+    name: &'static str,
+    lookup: LookupData,
+    detour: *const (),
+    /// Type of the hooked function, `None` for [`HookSet::raw`] hooks
+    fn_type: Option<TypeId>,
+    installed: Option<RawDetour>,
+}
+
+impl Drop for HookSet {
+    /// `RawDetour` only disables itself on drop with debug assertions, so it's done here before
+    /// the trampolines are freed
+    fn drop(&mut self) {
+        // Nothing can be done about a hook that fails to be disabled
+        let _ = unsafe { self.disable() };
+    }
+}
+
+// The pointers are to functions, and `RawDetour` is only toggled through `unsafe` methods
+unsafe impl Send for HookSet {}
+unsafe impl Sync for HookSet {}
+
+impl HookSet {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Load the modules of the hooks if they aren't loaded yet, instead of failing with
+    /// [`Error::ModuleNotLoaded`]
+    pub fn load_if_missing(mut self) -> Self {
+        self.load = ModuleLoad::LoadIfMissing;
+        self
+    }
+
+    /// Adds a hook of a function of type `F`, which `detour` is called instead of
+    pub fn hook<F: Function>(mut self, name: &'static str, lookup: LookupData, detour: F) -> Self {
+        self.hooks.push(RuntimeHook {
+            name,
+            lookup,
+            detour: detour.to_ptr(),
+            fn_type: Some(TypeId::of::<F>()),
+            installed: None,
+        });
+        self
+    }
+
+    /// Adds a hook without a function type, see [`HookSet::trampoline`] to call the original function
+    pub fn raw(mut self, name: &'static str, lookup: LookupData, detour: *const ()) -> Self {
+        self.hooks.push(RuntimeHook {
+            name,
+            lookup,
+            detour,
+            fn_type: None,
+            installed: None,
+        });
+        self
+    }
+
+    /// Installs and enables every hook that isn't installed yet. A hook failing doesn't stop the others
+    /// from being installed, instead every failure is listed in the returned [`Error::InitFailed`]
+    ///
+    /// # Safety
+    /// Each lookup must resolve to a function of the type its hook was added with
+    pub unsafe fn init(&mut self) -> Result<InitReport> {
+        let mut report = InitReport::default();
+        for hook in self
+            .hooks
+            .iter_mut()
+            .filter(|hook| hook.installed.is_none())
+        {
+            let (address, outcome) = match hook.lookup.resolve(self.load) {
+                Ok(addr) => {
+                    let outcome = RawDetour::new(addr, hook.detour).and_then(|detour| {
+                        detour.enable()?;
+                        hook.installed = Some(detour);
+                        Ok(())
+                    });
+                    (Some(addr as usize), outcome.map_err(Error::from))
+                }
+                Err(e) => (None, Err(e)),
+            };
+            report.hooks.push(HookReport {
+                detour_name: hook.name,
+                lookup: hook.lookup,
+                address,
+                optional: false,
+                outcome,
+            });
+        }
+        report.into_result()
+    }
+
+    /// Enables every installed hook
+    ///
+    /// # Safety
+    /// Same as [`retour::RawDetour::enable`]
+    pub unsafe fn enable(&self) -> Result<()> {
+        self.toggle(|detour| detour.enable())
+    }
+
+    /// Disables every installed hook
+    ///
+    /// # Safety
+    /// Same as [`retour::RawDetour::disable`]
+    pub unsafe fn disable(&self) -> Result<()> {
+        self.toggle(|detour| detour.disable())
+    }
+
+    /// Calls `toggle_fn` on every installed hook, even if one fails, returning the first error
+    unsafe fn toggle(&self, toggle_fn: unsafe fn(&RawDetour) -> retour::Result<()>) -> Result<()> {
+        let mut result = Ok(());
+        for detour in self.hooks.iter().filter_map(|hook| hook.installed.as_ref()) {
+            if let Err(e) = toggle_fn(detour) {
+                if result.is_ok() {
+                    result = Err(e.into());
+                }
+            }
+        }
+        result
+    }
+
+    /// Whether any of the hooks is currently enabled
+    pub fn is_enabled(&self) -> bool {
+        self.hooks
+            .iter()
+            .filter_map(|hook| hook.installed.as_ref())
+            .any(RawDetour::is_enabled)
+    }
+
+    /// The original function of the installed hook called `name`, if it was added with [`HookSet::hook`]
+    /// with the same function type
+    pub fn original<F: Function>(&self, name: &str) -> Option<F> {
+        let hook = self.hooks.iter().find(|hook| hook.name == name)?;
+        if hook.fn_type != Some(TypeId::of::<F>()) {
+            return None;
+        }
+        let trampoline = self.trampoline(name)?;
+        Some(unsafe { F::from_ptr(trampoline) })
+    }
+
+    /// Pointer to call the original function of the installed hook called `name` through
+    pub fn trampoline(&self, name: &str) -> Option<*const ()> {
+        let hook = self.hooks.iter().find(|hook| hook.name == name)?;
+        let detour = hook.installed.as_ref()?;
+        Some(detour.trampoline() as *const ())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::hint::black_box;
+
+    use super::*;
+
+    type AddOneFn = extern "C" fn(i32) -> i32;
+
+    #[inline(never)]
+    extern "C" fn add_one(x: i32) -> i32 {
+        black_box(x) + 1
+    }
+
+    extern "C" fn add_two(x: i32) -> i32 {
+        x + 2
+    }
+
+    #[test]
+    fn disables_hooks_on_drop() {
+        let add_one_fn: AddOneFn = black_box(add_one);
+        let mut hooks = HookSet::new();
+        hooks.hooks.push(RuntimeHook {
+            name: "add_one",
+            lookup: LookupData::from_offset("unused", 0),
+            detour: add_two as AddOneFn as *const (),
+            fn_type: Some(TypeId::of::<AddOneFn>()),
+            installed: Some(
+                unsafe { RawDetour::new(add_one_fn as *const (), add_two as *const ()) }.unwrap(),
+            ),
+        });
+        unsafe { hooks.enable() }.unwrap();
+        assert_eq!(add_one_fn(1), 3);
+
+        drop(hooks);
+        assert_eq!(add_one_fn(1), 2);
+    }
+}
@@ -1,23 +1,173 @@
+#![feature(unboxed_closures, tuple_trait, link_llvm_intrinsics)]
+// `link_llvm_intrinsics` gives `HookCtx::return_address` the address a hooked function was called from,
+// which has no stable equivalent. The lint can only be allowed for the whole crate
+#![allow(internal_features)]
+
+mod context;
+mod deferred;
 mod error;
+mod hook_set;
+mod module;
+mod pattern;
+mod registry;
+mod report;
 
-use std::{ffi::CString, path::Path};
+use std::{ffi::CString, fmt::Display, marker::Tuple, path::Path};
 
+pub use context::HookCtx;
+pub use deferred::{defer_detours, deferred_status, on_deferred_install, DeferredStatus};
 pub use error::Error;
+pub use hook_set::HookSet;
 use minidl::Library;
+pub use module::ModuleInfo;
+use pattern::Pattern;
+use retour::{Function, StaticDetour};
+#[doc(hidden)]
+pub use registry::{HOOK_MODULES, REGISTERED_HOOKS};
+pub use registry::{
+    hook_modules, init_all, registered_hooks, HookDescriptor, HookModule, ModuleDescriptor,
+    RegisteredHook,
+};
+pub use report::{HookReport, InitReport};
+
+#[doc(hidden)]
+pub mod __private {
+    pub use crate::context::enter_hook;
+    pub use linkme;
+}
 /// Macro used to hook multiple `retour::StaticDetour`s at once
 ///
 /// Reads a `mod` block and generating static detours from #[hook] macros.
-/// #[hook] is not its own macro that gets expanded, it is read and removed by the [`hook_module`]
+/// Inside a hook module, #[hook] is not expanded on its own, it is read and removed by the [`hook_module`].
+/// Outside of one, see [`hook`]
+///
+/// # `#[hook_module]` syntax
+/// ```ignore
+/// #[hook_module("MODULE_NAME", <options>)]
+/// ```
+/// By default, `init_detours` only hooks a module that is already loaded in the process and
+/// returns [`Error::ModuleNotLoaded`] otherwise. Options:
+/// - `load_if_missing`: load the module (`LoadLibraryW` / `dlopen`) if it isn't loaded yet
+/// - `deferred`: if the module isn't loaded yet, `init_detours` returns `Ok` right away and the
+///   hooks are installed as soon as the module gets loaded, see [`defer_detours`]
+/// - `transactional`: if any hook fails, every hook of the module that was installed is disabled
+///   again before `init_detours` returns the error, see [`InitReport::roll_back_on_failure`]
+/// - `detour_names = "{}_Detour"`: how `StaticDetour`s are named when `#[hook]` doesn't name them,
+///   `{}` being replaced by the hook function's name. Defaults to `"{}_Detour"`
 ///
 /// # `#[hook] syntax
 /// Hook based off symbol
 /// ```ignore
 /// #[hook(<unsafe> <extern> <ABI> DETOUR_NAME, symbol = "SYMBOL_NAME")]
 /// ```
-/// Hook based off offset
+/// Hook based off offset from the module's base address ([`ModuleInfo::base`])
 /// ```ignore
 /// #[hook(<unsafe> <extern> <ABI> DETOUR_NAME, offset = 0xDEAD_BEEF)]
 /// ```
+/// Hook based off a byte pattern, searched for in the module's executable sections (`??` is a wildcard)
+/// ```ignore
+/// #[hook(<unsafe> <extern> <ABI> DETOUR_NAME, pattern = "48 89 5C 24 ?? 57 48 83 EC 20")]
+/// ```
+/// Hook the target of a `call`/`jmp rel32` or `lea reg, [rip+disp32]` found by a byte pattern,
+/// with the displacement starting at the given byte of the match
+/// ```ignore
+/// #[hook(<unsafe> <extern> <ABI> DETOUR_NAME, pattern = "E8 ?? ?? ?? ?? 48 8B D8", follow = rel32(1))]
+/// ```
+/// The detour name and the lookup can be left out, in which case the detour is named after the hook
+/// function (see `detour_names` above) and the symbol with the same name as the hook function is hooked
+/// ```ignore
+/// #[hook(<unsafe> <extern> <ABI>)]
+/// #[hook(<unsafe> <extern> <ABI> DETOUR_NAME)]
+/// #[hook(<unsafe> <extern> <ABI>, offset = 0xDEAD_BEEF)]
+/// ```
+/// Any of these can be marked `optional`, so that if the symbol or pattern can't be found the hook is
+/// skipped and reported in [`InitReport::skipped`] instead of failing `init_detours`
+/// ```ignore
+/// #[hook(<unsafe> <extern> <ABI> DETOUR_NAME, symbol = "SYMBOL_NAME", optional)]
+/// ```
+/// Hooks can be tagged with a group, so that `init_group`, `enable_group` and `disable_group` handle
+/// every hook of that group at once
+/// ```ignore
+/// #[hook(<unsafe> <extern> <ABI> DETOUR_NAME, symbol = "SYMBOL_NAME", group = "GROUP_NAME")]
+/// ```
+/// Hooks can also be methods in an `impl` block, in which case `&self`/`&mut self` is the hooked function's
+/// first argument, typically the `this` pointer of a C++ member function.
+/// The `StaticDetour` takes it as `*const Self`/`*mut Self`, so `Self` should be `#[repr(C)]`
+/// ```ignore
+/// impl Player {
+///     #[hook(unsafe <extern> <ABI> DETOUR_NAME, symbol = "SYMBOL_NAME")]
+///     fn take_damage(&mut self, amount: i32) { ... }
+/// }
+/// ```
+/// `extern "thiscall"` hooks need the `thiscall-abi` feature, which requires nightly and x86.
+///
+/// Arguments and return types can borrow as `&T`, `&mut T`, `Option<&T>` or `Option<&mut T>`, with named
+/// or elided lifetimes. retour can't detour higher-ranked function types such as `for<'a> fn(&'a T)`,
+/// so the `StaticDetour` takes these as `*const T`/`*mut T` instead, and calling the original
+/// function through it takes and returns pointers. Such hooks (including methods) must be `unsafe`,
+/// since a safe `StaticDetour` would let safe code pass any pointer as a borrow
+/// ```ignore
+/// #[hook(unsafe extern "C" GmTime_Detour, symbol = "gmtime_r")]
+/// fn gmtime<'a>(time: &i64, tm: &'a mut Tm) -> Option<&'a mut Tm> { ... }
+/// ```
+///
+/// Functions declared in an `extern` block of the module, such as bindgen output, can be hooked by
+/// naming the declaration. The ABI, argument types and symbol (or `#[link_name]`) then come from the
+/// declaration, the detour is named after it, and the hook function only needs a matching body
+/// ```ignore
+/// extern "C" {
+///     fn lua_settop(L: *mut lua_State, idx: c_int);
+/// }
+///
+/// // Creates `lua_settop_Detour`, hooking the `lua_settop` symbol
+/// #[hook(lua_settop)]
+/// fn settop(l: *mut lua_State, idx: c_int) { ... }
+/// ```
+///
+/// `check = PATH` fails to compile unless the `StaticDetour`'s function type (ABI, unsafety, argument and
+/// return types, with borrows as pointers) is exactly the type of the referenced function, such as a
+/// bindgen declaration, since a mismatch otherwise only shows up as crashes at runtime.
+/// A safe function referenced by an `unsafe` hook fails with "unnecessary `unsafe` block"
+/// ```ignore
+/// #[hook(unsafe extern "C" DETOUR_NAME, symbol = "lua_newstate", check = crate::ffi::lua_newstate)]
+/// fn newstate(f: lua_Alloc, ud: *mut c_void) -> *mut lua_State { ... }
+/// ```
+///
+/// `#[cfg]`s on a hook function (or its `impl` block), and `#[cfg_attr]`s that expand to `#[cfg]`s, apply
+/// to everything generated for the hook, and its doc comments document its `StaticDetour`
+/// ```ignore
+/// /// Only hooked on Windows
+/// #[cfg(windows)]
+/// #[hook(unsafe extern "system" DETOUR_NAME, symbol = "SYMBOL_NAME")]
+/// fn hook() { ... }
+/// ```
+///
+/// A hook function can take a [`HookCtx`] as its first argument (or first after `&self`), which isn't part of
+/// the hooked function's type. It calls the original function, and gives the hook's [`HookDescriptor`],
+/// the address the hooked function was called from and how many times the hook was called
+/// ```ignore
+/// #[hook(unsafe extern "C" Settop_Detour, symbol = "lua_settop")]
+/// fn settop(ctx: &HookCtx<unsafe extern "C" fn(*mut lua_State, c_int)>, l: *mut lua_State, idx: c_int) {
+///     println!("call {} from {:#x}", ctx.call_number(), ctx.return_address());
+///     unsafe { ctx.call(l, idx) }
+/// }
+/// ```
+///
+/// Hooks can also be in `mod` blocks nested in the hook module. Each nested module with hooks gets its own
+/// `StaticDetour`s, `HOOKS`, `init_detours` and the rest of the generated items listed below, which the
+/// parent module's `init_detours`, `enable_detours`, `disable_detours`, `is_enabled` and group functions
+/// chain into. `mod` declarations pointing to another file are left as is, since the macro can't see them
+/// ```ignore
+/// #[hook_module("lua52.dll")]
+/// mod lua {
+///     // `lua::init_detours()` initializes `lua::gc::Collect_Detour` too
+///     mod gc {
+///         #[hook(unsafe extern "C" Collect_Detour, symbol = "lua_gc")]
+///         fn collect(l: *mut lua_State, what: c_int, data: c_int) -> c_int { ... }
+///     }
+/// }
+/// ```
+///
 /// Examples:
 /// ```ignore
 /// #[hook(unsafe extern "system" MessageBoxA_Detour, symbol = "MessageBoxA")]
@@ -26,8 +176,23 @@ use minidl::Library;
 /// #[hook(Add_Detour, symbol = "add")]
 /// fn add(a: i32, b: i32) -> i32 { ... }
 ///
+/// // Creates `lua_pushnumber_Detour`, hooking the `lua_pushnumber` symbol
+/// #[hook(unsafe extern "C")]
+/// fn lua_pushnumber(l: *mut lua_State, n: f64) { ... }
+///
 /// #[hook(lua_newstate_Detour, offset = 0x4321)]
 /// fn add(a: i32, b: i32) -> i32 { ... }
+///
+/// #[hook(lua_settop_Detour, pattern = "48 89 5C 24 ?? 57 48 83 EC 20")]
+/// fn settop(l: *mut lua_State, idx: i32) { ... }
+///
+/// #[hook(lua_setwarnf_Detour, symbol = "lua_setwarnf", optional)]
+/// fn setwarnf(l: *mut lua_State, f: lua_WarnFunction, ud: *mut c_void) { ... }
+///
+/// impl Player {
+///     #[hook(unsafe extern "thiscall" Player_TakeDamage_Detour, symbol = "?TakeDamage@Player@@QAEXH@Z")]
+///     fn take_damage(&mut self, amount: i32) { ... }
+/// }
 /// ```
 ///
 ///
@@ -49,15 +214,51 @@ use minidl::Library;
 ///     // == Generated by macro: ==
 ///     // #[hook_module] will create `MODULE_NAME`:
 ///     const MODULE_NAME: &str = "lua52.dll"
+///     // a `HOOKS` table describing each hook (names, ABI, lookup, resolved address, enabled state),
+///     // which is also listed by `retour_utils::hook_modules()`:
+///     pub static HOOKS: [retour_utils::HookDescriptor; 1] = [..];
 ///     // and init_detours function
-///     pub unsafe init_detours() -> crate::Result<()> {..}
-///     // which will initialize all the StaticDetours generated by the macro inside this module
+///     pub unsafe init_detours() -> Result<retour_utils::InitReport, retour_utils::Error> {..}
+///     // which will initialize all the StaticDetours generated by the macro inside this module.
+///     // A hook failing doesn't stop the others from being initialized, instead every failure
+///     // is listed in the returned `Error::InitFailed`
+///     // Once initialized, all the StaticDetours can be toggled with
+///     pub unsafe fn enable_detours() -> Result<(), retour_utils::Error> {..}
+///     pub unsafe fn disable_detours() -> Result<(), retour_utils::Error> {..}
+///     // and `is_enabled` tells whether any of them is currently enabled
+///     pub fn is_enabled() -> bool {..}
+///     // If any hook has a `group = "..."`, the hooks of a group can be handled on their own with
+///     pub unsafe fn init_group(group: &str) -> Result<retour_utils::InitReport, retour_utils::Error> {..}
+///     pub unsafe fn enable_group(group: &str) -> Result<(), retour_utils::Error> {..}
+///     pub unsafe fn disable_group(group: &str) -> Result<(), retour_utils::Error> {..}
+///     // Finally, a `Module` marker type implements `retour_utils::HookModule` with all of the above
+///     pub struct Module;
 /// }
 /// ```
 pub use retour_utils_impl::hook_module;
 
+/// Hooks a function outside of a [`hook_module`], so hooks can be spread across the crate
+///
+/// Takes the module first, then the same arguments as `#[hook]` in a [`hook_module`].
+/// The `StaticDetour` is generated next to the function, and the hook is registered so that
+/// [`init_all`] initializes it, along with every `#[hook_module]` linked into the final binary
+/// ```ignore
+/// #[hook(module = "lua52.dll", unsafe extern "C" Settop_Detour, symbol = "lua_settop")]
+/// fn settop(l: *mut lua_State, idx: c_int) {
+///     unsafe { Settop_Detour.call(l, idx) }
+/// }
+///
+/// fn main() {
+///     unsafe { retour_utils::init_all() }.unwrap();
+/// }
+/// ```
+/// Standalone hooks are listed by [`registered_hooks`]. Extern block declarations aren't visible to
+/// them, and neither are module options such as `load_if_missing` or `deferred`
+pub use retour_utils_impl::hook;
+
 type Result<T> = std::result::Result<T, error::Error>;
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum LookupData {
     Offset {
         module: &'static str,
@@ -67,6 +268,46 @@ pub enum LookupData {
         module: &'static str,
         symbol: &'static str,
     },
+    /// Space separated hex bytes with `??` wildcards, e.g. `"48 89 5C 24 ?? 57"`,
+    /// that must match exactly one location in the module's executable sections
+    Pattern {
+        module: &'static str,
+        pattern: &'static str,
+        follow: Option<Follow>,
+    },
+}
+
+/// Extra resolution step applied to the address matched by a [`LookupData::Pattern`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Follow {
+    /// Read the `i32` displacement starting this many bytes into the match, and resolve it
+    /// relative to the end of the displacement.
+    ///
+    /// This is how `call rel32` (`E8`), `jmp rel32` (`E9`) and `lea reg, [rip+disp32]` encode their
+    /// target, as long as the displacement is the last part of the instruction.
+    /// The 4 bytes of the displacement must be part of the pattern.
+    Rel32(usize),
+}
+
+impl Follow {
+    /// Whether the bytes read after a match of `pattern_len` bytes are part of the match
+    fn fits(&self, pattern_len: usize) -> bool {
+        match self {
+            Follow::Rel32(position) => position
+                .checked_add(4)
+                .is_some_and(|end| end <= pattern_len),
+        }
+    }
+
+    unsafe fn resolve(&self, addr: *const u8) -> *const u8 {
+        match self {
+            Follow::Rel32(position) => {
+                let displacement = addr.add(*position);
+                let rel = std::ptr::read_unaligned(displacement as *const i32);
+                (displacement as usize + 4).wrapping_add_signed(rel as isize) as *const u8
+            }
+        }
+    }
 }
 
 impl LookupData {
@@ -78,43 +319,251 @@ impl LookupData {
         Self::Symbol { module, symbol }
     }
 
-    fn get_module(&self) -> &str {
+    pub const fn from_pattern(module: &'static str, pattern: &'static str) -> Self {
+        Self::Pattern {
+            module,
+            pattern,
+            follow: None,
+        }
+    }
+
+    pub const fn from_pattern_follow(
+        module: &'static str,
+        pattern: &'static str,
+        follow: Follow,
+    ) -> Self {
+        Self::Pattern {
+            module,
+            pattern,
+            follow: Some(follow),
+        }
+    }
+
+    pub fn get_module(&self) -> &'static str {
         match self {
             Self::Offset { module, .. } => module,
             Self::Symbol { module, .. } => module,
+            Self::Pattern { module, .. } => module,
         }
     }
 
-    fn address_from_handle(&self, handle: &Library) -> Option<*const ()> {
+    fn address_from_handle(&self, handle: &Library) -> Result<*const ()> {
+        let module_info = || unsafe { ModuleInfo::from_handle(handle.as_ptr()) };
         match self {
             LookupData::Offset { offset, .. } => {
-                Some((handle.as_ptr() as usize + offset) as *const ())
+                let module_info = module_info().ok_or(Error::ModuleNotLoaded)?;
+                Ok((module_info.base + offset) as *const ())
             }
             LookupData::Symbol { symbol, .. } => {
-                let c_symbol = CString::new(*symbol).ok()?;
-                let symbol_with_null_terminator =
-                    String::from_utf8(c_symbol.into_bytes_with_nul()).ok()?;
+                let symbol_with_null_terminator = CString::new(*symbol)
+                    .ok()
+                    .and_then(|c_symbol| String::from_utf8(c_symbol.into_bytes_with_nul()).ok())
+                    .ok_or(Error::SymbolNotFound)?;
 
-                unsafe { handle.sym_opt(&symbol_with_null_terminator) }
+                unsafe { handle.sym_opt(&symbol_with_null_terminator) }.ok_or(Error::SymbolNotFound)
+            }
+            LookupData::Pattern {
+                pattern, follow, ..
+            } => {
+                let pattern = Pattern::parse(pattern)?;
+                if follow.is_some_and(|follow| !follow.fits(pattern.len())) {
+                    return Err(Error::InvalidPattern);
+                }
+                let module_info = module_info().ok_or(Error::ModuleNotLoaded)?;
+                let addr = pattern.find_unique(&module_info.executable_regions())?;
+                let addr = match follow {
+                    Some(follow) => unsafe { follow.resolve(addr) },
+                    None => addr,
+                };
+                Ok(addr as *const ())
             }
         }
     }
 }
 
+impl Display for LookupData {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LookupData::Offset { module, offset } => write!(f, "{module}+{offset:#x}"),
+            LookupData::Symbol { module, symbol } => write!(f, "{module}!{symbol}"),
+            LookupData::Pattern {
+                module,
+                pattern,
+                follow,
+            } => {
+                write!(f, "{module} pattern \"{pattern}\"")?;
+                match follow {
+                    Some(Follow::Rel32(position)) => write!(f, " follow rel32({position})"),
+                    None => Ok(()),
+                }
+            }
+        }
+    }
+}
+
+/// How the module containing a hook's target is found
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ModuleLoad {
+    /// Only hook a module that is already loaded in the process,
+    /// failing with [`Error::ModuleNotLoaded`] otherwise
+    #[default]
+    Attach,
+    /// Load the module if it isn't loaded yet (`LoadLibraryW` / `dlopen`)
+    LoadIfMissing,
+}
+
+impl LookupData {
+    /// Get the address of the function being looked up
+    pub fn resolve(&self, load: ModuleLoad) -> Result<*const ()> {
+        // Get handle to module (aka dll / so)
+        let module = Path::new(self.get_module());
+        let handle = match load {
+            ModuleLoad::Attach => module::loaded_library(module),
+            ModuleLoad::LoadIfMissing => Library::load(module).ok(),
+        };
+        let handle = handle.ok_or(Error::ModuleNotLoaded)?;
+        self.address_from_handle(&handle)
+    }
+}
+
 /// Initialize detour by passing the address of original function to `init_detour_fn`
 ///
-/// This is called by `init_detours`, which is generated by the [`hook_module`] macro
+/// The module must already be loaded in the process, see [`load_and_init_detour`] otherwise.
+///
+/// # Safety
+/// `lookup_data` must resolve to a function whose type matches the detour initialized by `init_detour_fn`
 pub unsafe fn init_detour(
     lookup_data: LookupData,
     init_detour_fn: fn(*const ()) -> retour::Result<()>,
 ) -> Result<()> {
-    // Get handle to module (aka dll / so)
-    if let Ok(handle) = Library::load(Path::new(lookup_data.get_module())) {
-        let Some(addr) = lookup_data.address_from_handle(&handle) else {
-            return Err(Error::ModuleNotLoaded);
-        };
-        init_detour_fn(addr)?;
-    }
+    let addr = lookup_data.resolve(ModuleLoad::Attach)?;
+    init_detour_fn(addr)?;
+
+    Ok(())
+}
+
+/// Same as [`init_detour`], but loads the module first if it isn't loaded yet
+///
+/// # Safety
+/// `lookup_data` must resolve to a function whose type matches the detour initialized by `init_detour_fn`
+pub unsafe fn load_and_init_detour(
+    lookup_data: LookupData,
+    init_detour_fn: fn(*const ()) -> retour::Result<()>,
+) -> Result<()> {
+    let addr = lookup_data.resolve(ModuleLoad::LoadIfMissing)?;
+    init_detour_fn(addr)?;
 
     Ok(())
 }
+
+/// Initialize `detour` with `closure` as its detour function, and enable it
+///
+/// Unlike the functions generated by [`hook_module`], the closure can capture state, e.g. per-session
+/// configuration. It can be replaced while the hook stays installed with [`StaticDetour::set_detour`].
+/// The `StaticDetour` can be one declared by `#[hook]`, but then its module's `init_detours` (and
+/// [`init_all`]) fail for it with [`retour::Error::AlreadyInitialized`], and its entry in `HOOKS` isn't
+/// updated by `install_closure`, so [`HookDescriptor::address`] is `None`.
+/// The module must already be loaded in the process
+///
+/// ```ignore
+/// let scale = config.scale;
+/// install_closure(&Lua_pushnumber, LookupData::from_symbol("lua52.dll", "lua_pushnumber"), move |l, n| {
+///     Lua_pushnumber.call(l, n * scale)
+/// })?;
+/// // Later on, without unhooking
+/// Lua_pushnumber.set_detour(|l, n| Lua_pushnumber.call(l, n));
+/// ```
+///
+/// # Safety
+/// `lookup_data` must resolve to a function of type `T`
+pub unsafe fn install_closure<T, C>(
+    detour: &StaticDetour<T>,
+    lookup_data: LookupData,
+    closure: C,
+) -> Result<()>
+where
+    T: Function,
+    T::Arguments: Tuple,
+    C: Fn<T::Arguments, Output = T::Output> + Send + 'static,
+{
+    let addr = lookup_data.resolve(ModuleLoad::Attach)?;
+    detour.initialize(T::from_ptr(addr), closure)?.enable()?;
+
+    Ok(())
+}
+
+/// Initialize a detour like [`init_detour`], but report the outcome instead of returning early
+///
+/// This is called for every hook by `init_detours`, which is generated by the [`hook_module`] macro.
+/// The resolved address is recorded in `hook`. If the hook is optional, it's skipped instead of failing
+/// when its lookup can't be resolved, see [`HookReport::is_skipped`]
+///
+/// # Safety
+/// `hook.lookup` must resolve to a function whose type matches the detour initialized by `init_detour_fn`
+pub unsafe fn init_hook(
+    hook: &'static HookDescriptor,
+    load: ModuleLoad,
+    init_detour_fn: fn(*const ()) -> retour::Result<()>,
+) -> HookReport {
+    let (address, outcome) = match hook.lookup.resolve(load) {
+        Ok(addr) => {
+            hook.set_address(addr as usize);
+            (
+                Some(addr as usize),
+                init_detour_fn(addr).map_err(Error::from),
+            )
+        }
+        Err(e) => (None, Err(e)),
+    };
+    HookReport {
+        detour_name: hook.detour_name,
+        lookup: hook.lookup,
+        address,
+        optional: hook.optional,
+        outcome,
+    }
+}
+
+/// Enable or disable a set of detours, skipping the ones that were never initialized
+///
+/// Every function in `toggle_fns` is called even if one fails, and the first error is returned.
+/// This is called by `enable_detours` and `disable_detours`, which are generated by the [`hook_module`] macro
+///
+/// # Safety
+/// Same as [`retour::StaticDetour::enable`] and [`retour::StaticDetour::disable`]
+pub unsafe fn toggle_detours(toggle_fns: &[unsafe fn() -> retour::Result<()>]) -> Result<()> {
+    let mut result = Ok(());
+    for toggle_fn in toggle_fns {
+        match toggle_fn() {
+            Ok(()) | Err(retour::Error::NotInitialized) => {}
+            Err(e) => {
+                if result.is_ok() {
+                    result = Err(e.into());
+                }
+            }
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn follows_backwards_rel32() {
+        // nop; call <8 bytes before the end of the call>
+        let mut code = [0x90, 0xE8, 0, 0, 0, 0];
+        code[2..].copy_from_slice(&(-8i32).to_le_bytes());
+        let follow = Follow::Rel32(1);
+        assert!(follow.fits(code.len() - 1));
+        let target = unsafe { follow.resolve(code[1..].as_ptr()) };
+        assert_eq!(target as usize, code.as_ptr() as usize + code.len() - 8);
+    }
+
+    #[test]
+    fn rejects_rel32_past_pattern() {
+        assert!(!Follow::Rel32(1).fits(4));
+        assert!(!Follow::Rel32(usize::MAX).fits(usize::MAX));
+    }
+}
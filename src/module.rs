@@ -0,0 +1,235 @@
+use std::{
+    ffi::c_void,
+    ops::Range,
+    path::{Path, PathBuf},
+};
+
+use minidl::Library;
+
+/// A module (aka dll / so) mapped into the current process
+#[derive(Debug, Clone)]
+pub struct ModuleInfo {
+    /// Address the module was loaded at.
+    ///
+    /// `offset` hooks are relative to this, so it matches the image base a disassembler shows
+    /// (`HMODULE` on Windows, the ELF load bias `link_map::l_addr` on Linux)
+    pub base: usize,
+    /// Size of the module's image in memory, starting at `base`
+    pub size: usize,
+    /// Path the module was loaded from
+    pub path: PathBuf,
+    executable: Vec<Range<usize>>,
+}
+
+impl ModuleInfo {
+    /// Get info of the module behind a raw library handle
+    ///
+    /// # Safety
+    /// `handle` must be an `HMODULE` on Windows, or a handle returned by `dlopen` on Unix,
+    /// for a module that stays loaded
+    pub unsafe fn from_handle(handle: *mut c_void) -> Option<Self> {
+        if handle.is_null() {
+            return None;
+        }
+        platform::module_info(handle)
+    }
+
+    /// Get info of a module that is already loaded in the current process, without loading it
+    pub fn find(name: impl AsRef<Path>) -> Option<Self> {
+        let handle = loaded_library(name.as_ref())?;
+        unsafe { Self::from_handle(handle.as_ptr()) }
+    }
+
+    /// Memory of every executable section/segment of the module
+    pub(crate) fn executable_regions(&self) -> Vec<&'static [u8]> {
+        self.executable
+            .iter()
+            .map(|region| unsafe {
+                std::slice::from_raw_parts(region.start as *const u8, region.len())
+            })
+            .collect()
+    }
+}
+
+/// Get a handle to a module only if it is already loaded in the current process
+#[cfg(windows)]
+pub(crate) fn loaded_library(name: &Path) -> Option<Library> {
+    use std::os::windows::ffi::OsStrExt;
+
+    #[link(name = "kernel32")]
+    extern "system" {
+        fn GetModuleHandleW(module_name: *const u16) -> *mut c_void;
+    }
+
+    let name: Vec<u16> = name.as_os_str().encode_wide().chain([0]).collect();
+    unsafe { Library::from_ptr(GetModuleHandleW(name.as_ptr())) }
+}
+
+/// Get a handle to a module only if it is already loaded in the current process
+#[cfg(unix)]
+pub(crate) fn loaded_library(name: &Path) -> Option<Library> {
+    use std::{ffi::CString, os::unix::ffi::OsStrExt};
+
+    let name = CString::new(name.as_os_str().as_bytes()).ok()?;
+    unsafe {
+        let handle = libc::dlopen(name.as_ptr(), libc::RTLD_LAZY | libc::RTLD_NOLOAD);
+        Library::from_ptr(handle)
+    }
+}
+
+#[cfg(windows)]
+mod platform {
+    use std::{
+        ffi::{c_void, OsString},
+        os::windows::ffi::OsStringExt,
+    };
+
+    use super::ModuleInfo;
+
+    const IMAGE_SCN_MEM_EXECUTE: u32 = 0x2000_0000;
+    const MAX_PATH_LEN: usize = 32 * 1024;
+
+    #[link(name = "kernel32")]
+    extern "system" {
+        fn GetModuleFileNameW(module: *mut c_void, filename: *mut u16, size: u32) -> u32;
+    }
+
+    unsafe fn read<T: Copy>(addr: usize) -> T {
+        std::ptr::read_unaligned(addr as *const T)
+    }
+
+    /// Walk the headers of the PE image mapped at the module handle (`HMODULE` is the image base)
+    pub unsafe fn module_info(handle: *mut c_void) -> Option<ModuleInfo> {
+        let base = handle as usize;
+        // IMAGE_DOS_HEADER::e_magic / e_lfanew
+        if read::<u16>(base) != 0x5A4D {
+            return None;
+        }
+        let nt_headers = base + read::<u32>(base + 0x3C) as usize;
+        // IMAGE_NT_HEADERS::Signature
+        if read::<u32>(nt_headers) != 0x0000_4550 {
+            return None;
+        }
+        let file_header = nt_headers + 4;
+        let number_of_sections = read::<u16>(file_header + 2) as usize;
+        let size_of_optional_header = read::<u16>(file_header + 16) as usize;
+        let optional_header = file_header + 20;
+        let size_of_image = read::<u32>(optional_header + 56) as usize;
+        let section_headers = optional_header + size_of_optional_header;
+
+        let executable = (0..number_of_sections)
+            .map(|i| section_headers + i * 40)
+            .filter(|section| read::<u32>(section + 36) & IMAGE_SCN_MEM_EXECUTE != 0)
+            .map(|section| {
+                let virtual_size = read::<u32>(section + 8) as usize;
+                let virtual_address = read::<u32>(section + 12) as usize;
+                base + virtual_address..base + virtual_address + virtual_size
+            })
+            .collect();
+
+        let mut filename = vec![0u16; MAX_PATH_LEN];
+        let len = GetModuleFileNameW(handle, filename.as_mut_ptr(), filename.len() as u32);
+        filename.truncate(len as usize);
+
+        Some(ModuleInfo {
+            base,
+            size: size_of_image,
+            path: OsString::from_wide(&filename).into(),
+            executable,
+        })
+    }
+}
+
+#[cfg(target_os = "linux")]
+mod platform {
+    use std::{
+        ffi::{c_char, c_int, c_void, CStr, OsStr},
+        os::unix::ffi::OsStrExt,
+        path::PathBuf,
+    };
+
+    use super::ModuleInfo;
+
+    /// Leading fields of glibc/musl's `struct link_map`, which is what `dlopen` hands back
+    #[repr(C)]
+    struct LinkMap {
+        l_addr: usize,
+        l_name: *const c_char,
+    }
+
+    struct Search<'a> {
+        link_map: &'a LinkMap,
+        info: Option<ModuleInfo>,
+    }
+
+    unsafe extern "C" fn callback(
+        info: *mut libc::dl_phdr_info,
+        _size: libc::size_t,
+        data: *mut c_void,
+    ) -> c_int {
+        let info = &*info;
+        let search = &mut *(data as *mut Search);
+        if info.dlpi_addr as usize != search.link_map.l_addr
+            || info.dlpi_name.is_null()
+            || CStr::from_ptr(info.dlpi_name) != CStr::from_ptr(search.link_map.l_name)
+        {
+            return 0;
+        }
+        let base = info.dlpi_addr as usize;
+        let loads = std::slice::from_raw_parts(info.dlpi_phdr, info.dlpi_phnum as usize)
+            .iter()
+            .filter(|phdr| phdr.p_type == libc::PT_LOAD);
+        let size = loads
+            .clone()
+            .map(|phdr| (phdr.p_vaddr + phdr.p_memsz) as usize)
+            .max()
+            .unwrap_or_default();
+        let executable = loads
+            .filter(|phdr| phdr.p_flags & libc::PF_X != 0 && phdr.p_flags & libc::PF_R != 0)
+            .map(|phdr| {
+                let start = base + phdr.p_vaddr as usize;
+                start..start + phdr.p_memsz as usize
+            })
+            .collect();
+        // The main program is listed with an empty name
+        let name = CStr::from_ptr(info.dlpi_name).to_bytes();
+        let path = if name.is_empty() {
+            std::env::current_exe().unwrap_or_default()
+        } else {
+            PathBuf::from(OsStr::from_bytes(name))
+        };
+
+        search.info = Some(ModuleInfo {
+            base,
+            size,
+            path,
+            executable,
+        });
+        1
+    }
+
+    /// Find the loaded object matching the `dlopen` handle with `dl_iterate_phdr`
+    pub unsafe fn module_info(handle: *mut c_void) -> Option<ModuleInfo> {
+        let link_map = &*(handle as *const LinkMap);
+        if link_map.l_name.is_null() {
+            return None;
+        }
+        let mut search = Search {
+            link_map,
+            info: None,
+        };
+        libc::dl_iterate_phdr(Some(callback), &mut search as *mut Search as *mut c_void);
+        search.info
+    }
+}
+
+#[cfg(not(any(windows, target_os = "linux")))]
+mod platform {
+    use std::ffi::c_void;
+
+    use super::ModuleInfo;
+
+    pub unsafe fn module_info(_handle: *mut c_void) -> Option<ModuleInfo> {
+        None
+    }
+}
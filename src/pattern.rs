@@ -0,0 +1,110 @@
+use crate::{error::Error, Result};
+
+/// Byte signature (aka AOB) where `None` matches any byte
+///
+/// Parsed from a string of space separated hex bytes, with `??` (or `?`) as a wildcard:
+/// ```text
+/// 48 89 5C 24 ?? 57 48 83 EC 20
+/// ```
+pub(crate) struct Pattern(Vec<Option<u8>>);
+
+impl Pattern {
+    pub fn parse(pattern: &str) -> Result<Self> {
+        let bytes = pattern
+            .split_whitespace()
+            .map(|byte| match byte {
+                "?" | "??" => Ok(None),
+                _ if byte.len() == 2 => u8::from_str_radix(byte, 16)
+                    .map(Some)
+                    .map_err(|_| Error::InvalidPattern),
+                _ => Err(Error::InvalidPattern),
+            })
+            .collect::<Result<Vec<_>>>()?;
+        if bytes.is_empty() {
+            return Err(Error::InvalidPattern);
+        }
+        Ok(Self(bytes))
+    }
+
+    /// Number of bytes matched, wildcards included
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    fn matches(&self, window: &[u8]) -> bool {
+        self.0
+            .iter()
+            .zip(window)
+            .all(|(expected, actual)| expected.is_none_or(|expected| expected == *actual))
+    }
+
+    /// Find the only location in `regions` matching this pattern
+    pub fn find_unique(&self, regions: &[&[u8]]) -> Result<*const u8> {
+        let mut found = None;
+        for region in regions {
+            for window in region.windows(self.0.len()) {
+                if !self.matches(window) {
+                    continue;
+                }
+                if found.is_some() {
+                    return Err(Error::PatternNotUnique);
+                }
+                found = Some(window.as_ptr());
+            }
+        }
+        found.ok_or(Error::PatternNotFound)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_wildcards() {
+        let pattern = Pattern::parse("48 ? 5c ??").unwrap();
+        assert_eq!(pattern.0, [Some(0x48), None, Some(0x5C), None]);
+        assert!(matches!(Pattern::parse(""), Err(Error::InvalidPattern)));
+        assert!(matches!(Pattern::parse("48 8"), Err(Error::InvalidPattern)));
+        assert!(matches!(Pattern::parse("48 XY"), Err(Error::InvalidPattern)));
+    }
+
+    #[test]
+    fn finds_unique_match() {
+        let region = [0x90, 0x48, 0x89, 0x5C, 0x24, 0x10, 0xC3];
+        let found = Pattern::parse("48 ? 5C ?? 10")
+            .unwrap()
+            .find_unique(&[&region])
+            .unwrap();
+        assert_eq!(found, region[1..].as_ptr());
+    }
+
+    #[test]
+    fn reports_missing_match() {
+        let region = [0x90, 0x48, 0x89, 0x5C];
+        let pattern = Pattern::parse("48 ?? 5C 24").unwrap();
+        assert!(matches!(
+            pattern.find_unique(&[&region]),
+            Err(Error::PatternNotFound)
+        ));
+        // Matches don't span regions
+        assert!(matches!(
+            pattern.find_unique(&[&region, &[0x24]]),
+            Err(Error::PatternNotFound)
+        ));
+    }
+
+    #[test]
+    fn reports_multiple_matches() {
+        let pattern = Pattern::parse("E8 ?? C3").unwrap();
+        let region = [0xE8, 0x01, 0xC3, 0xE8, 0x02, 0xC3];
+        assert!(matches!(
+            pattern.find_unique(&[&region]),
+            Err(Error::PatternNotUnique)
+        ));
+        assert!(matches!(
+            pattern.find_unique(&[&region[..3], &region[3..]]),
+            Err(Error::PatternNotUnique)
+        ));
+    }
+}
@@ -0,0 +1,193 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use linkme::distributed_slice;
+
+use crate::{Error, HookReport, InitReport, LookupData, Result};
+
+/// Every `#[hook_module]` linked into the final binary
+#[doc(hidden)]
+#[distributed_slice]
+pub static HOOK_MODULES: [ModuleDescriptor];
+
+/// Every `#[hook_module]` linked into the final binary, whether or not its hooks were initialized
+pub fn hook_modules() -> &'static [ModuleDescriptor] {
+    &HOOK_MODULES
+}
+
+/// Every standalone `#[hook(module = "...")]` linked into the final binary
+#[doc(hidden)]
+#[distributed_slice]
+pub static REGISTERED_HOOKS: [RegisteredHook];
+
+/// Every standalone `#[hook(module = "...")]` linked into the final binary, see [`hook`](crate::hook)
+pub fn registered_hooks() -> &'static [RegisteredHook] {
+    &REGISTERED_HOOKS
+}
+
+/// Initializes every hook linked into the final binary, both the standalone `#[hook(module = "...")]`s
+/// and those of every `#[hook_module]`. A hook failing doesn't stop the others from being initialized,
+/// instead every failure is listed in the returned [`Error::InitFailed`].
+/// Hooks whose `StaticDetour` was already initialized with [`install_closure`](crate::install_closure)
+/// are among the failures, with [`retour::Error::AlreadyInitialized`]
+///
+/// # Safety
+/// Same as [`init_hook`](crate::init_hook) for every hook
+pub unsafe fn init_all() -> Result<InitReport> {
+    let mut report = InitReport::default();
+    for hook in registered_hooks() {
+        report.hooks.push((hook.init)());
+    }
+    for module in hook_modules() {
+        let Some(init) = module.init else {
+            continue;
+        };
+        match init() {
+            Ok(module_report) | Err(Error::InitFailed(module_report)) => {
+                report.hooks.extend(module_report.hooks);
+            }
+            Err(e) => return Err(e),
+        }
+    }
+    report.into_result()
+}
+
+/// Implemented by the `Module` marker type that [`hook_module`](crate::hook_module) generates in
+/// every hook module, so hook modules can be handled generically
+///
+/// ```ignore
+/// let modules: Vec<&dyn HookModule> = vec![&lua::Module, &render::Module];
+/// for module in modules {
+///     unsafe { module.init() }?;
+/// }
+/// ```
+pub trait HookModule: Sync {
+    /// Name of the module being hooked, same as the generated `MODULE_NAME`
+    fn module_name(&self) -> &'static str;
+
+    /// Same as the generated `init_detours`
+    ///
+    /// # Safety
+    /// Same as [`init_hook`](crate::init_hook) for every hook of the module
+    unsafe fn init(&self) -> Result<InitReport>;
+
+    /// Same as the generated `enable_detours`
+    ///
+    /// # Safety
+    /// Same as [`retour::StaticDetour::enable`]
+    unsafe fn enable(&self) -> Result<()>;
+
+    /// Same as the generated `disable_detours`
+    ///
+    /// # Safety
+    /// Same as [`retour::StaticDetour::disable`]
+    unsafe fn disable(&self) -> Result<()>;
+
+    /// Same as the generated `is_enabled`
+    fn is_enabled(&self) -> bool;
+
+    /// Same as the generated `HOOKS` table
+    fn hooks(&self) -> &'static [HookDescriptor];
+}
+
+/// Describes a `#[hook_module]`, generated alongside its `MODULE_NAME`
+#[derive(Debug)]
+pub struct ModuleDescriptor {
+    /// Name of the module being hooked, e.g. `"lua52.dll"`
+    pub name: &'static str,
+    /// Rust path of the `mod` the hooks were declared in
+    pub path: &'static str,
+    /// The module's `HOOKS` table
+    pub hooks: &'static [HookDescriptor],
+    /// The module's `init_detours`, `None` for nested modules since their parent's chains into theirs
+    pub init: Option<unsafe fn() -> Result<InitReport>>,
+}
+
+/// Describes a standalone `#[hook(module = "...")]`, see [`hook`](crate::hook)
+#[derive(Debug)]
+pub struct RegisteredHook {
+    pub hook: &'static HookDescriptor,
+    /// Rust path of the `mod` the hook function is in
+    pub path: &'static str,
+    init: unsafe fn() -> HookReport,
+}
+
+impl RegisteredHook {
+    #[doc(hidden)]
+    pub const fn __new(
+        hook: &'static HookDescriptor,
+        path: &'static str,
+        init: unsafe fn() -> HookReport,
+    ) -> Self {
+        Self { hook, path, init }
+    }
+}
+
+/// Describes a single `#[hook]`, see the `HOOKS` table generated by [`hook_module`](crate::hook_module)
+#[derive(Debug)]
+pub struct HookDescriptor {
+    /// Name of the generated `StaticDetour`
+    pub detour_name: &'static str,
+    /// Name of the Rust function the hook calls
+    pub fn_name: &'static str,
+    /// ABI of the hooked function, `None` for the Rust ABI
+    pub abi: Option<&'static str>,
+    /// Where the hooked function is looked up
+    pub lookup: LookupData,
+    pub group: Option<&'static str>,
+    pub optional: bool,
+    address: AtomicUsize,
+    calls: AtomicUsize,
+    is_enabled: fn() -> bool,
+}
+
+impl HookDescriptor {
+    #[doc(hidden)]
+    pub const fn __new(
+        detour_name: &'static str,
+        fn_name: &'static str,
+        abi: Option<&'static str>,
+        lookup: LookupData,
+        group: Option<&'static str>,
+        optional: bool,
+        is_enabled: fn() -> bool,
+    ) -> Self {
+        Self {
+            detour_name,
+            fn_name,
+            abi,
+            lookup,
+            group,
+            optional,
+            address: AtomicUsize::new(0),
+            calls: AtomicUsize::new(0),
+            is_enabled,
+        }
+    }
+
+    /// Address the lookup resolved to, if the hook was initialized
+    pub fn address(&self) -> Option<usize> {
+        match self.address.load(Ordering::Relaxed) {
+            0 => None,
+            address => Some(address),
+        }
+    }
+
+    /// Whether the hook's `StaticDetour` is currently enabled
+    pub fn is_enabled(&self) -> bool {
+        (self.is_enabled)()
+    }
+
+    /// How many times the hook was called, only counted for hooks taking a [`HookCtx`](crate::HookCtx)
+    pub fn calls(&self) -> usize {
+        self.calls.load(Ordering::Relaxed)
+    }
+
+    /// Counts a call, returning how many there were including this one
+    pub(crate) fn count_call(&self) -> usize {
+        self.calls.fetch_add(1, Ordering::Relaxed) + 1
+    }
+
+    pub(crate) fn set_address(&self, address: usize) {
+        self.address.store(address, Ordering::Relaxed);
+    }
+}
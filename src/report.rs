@@ -0,0 +1,112 @@
+use std::fmt::Display;
+
+use crate::{error::Error, LookupData, Result};
+
+/// Outcome of initializing a single hook
+#[derive(Debug)]
+pub struct HookReport {
+    /// Name of the `StaticDetour` generated for the hook, or of the hook in a [`HookSet`](crate::HookSet)
+    pub detour_name: &'static str,
+    /// Where the hooked function was looked up
+    pub lookup: LookupData,
+    /// Address the lookup resolved to, if it got that far
+    pub address: Option<usize>,
+    /// Hook was marked `optional`, so failing to resolve it doesn't fail `init_detours`
+    pub optional: bool,
+    pub outcome: Result<()>,
+}
+
+impl HookReport {
+    pub fn is_ok(&self) -> bool {
+        self.outcome.is_ok()
+    }
+
+    /// Optional hook that wasn't installed because its symbol or pattern couldn't be resolved
+    pub fn is_skipped(&self) -> bool {
+        self.optional
+            && matches!(
+                self.outcome,
+                Err(Error::SymbolNotFound | Error::PatternNotFound | Error::PatternNotUnique)
+            )
+    }
+}
+
+impl Display for HookReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} ({})", self.detour_name, self.lookup)?;
+        if let Some(address) = self.address {
+            write!(f, " at {address:#x}")?;
+        }
+        match &self.outcome {
+            Ok(()) => f.write_str(": ok"),
+            Err(e) if self.is_skipped() => write!(f, ": skipped, {e}"),
+            Err(e) => write!(f, ": {e}"),
+        }
+    }
+}
+
+/// Outcome of every hook initialized by a `#[hook_module]`'s `init_detours`
+#[derive(Debug, Default)]
+pub struct InitReport {
+    pub hooks: Vec<HookReport>,
+}
+
+impl InitReport {
+    /// Hooks that failed to initialize, not counting skipped optional hooks
+    pub fn failures(&self) -> impl Iterator<Item = &HookReport> {
+        self.hooks
+            .iter()
+            .filter(|hook| !hook.is_ok() && !hook.is_skipped())
+    }
+
+    /// Optional hooks that were skipped, see [`HookReport::is_skipped`]
+    pub fn skipped(&self) -> impl Iterator<Item = &HookReport> {
+        self.hooks.iter().filter(|hook| hook.is_skipped())
+    }
+
+    pub fn is_ok(&self) -> bool {
+        self.failures().next().is_none()
+    }
+
+    /// If any hook failed, disable every hook that was installed and mark it [`Error::RolledBack`]
+    ///
+    /// `disable_fns` disables the detour of each hook, in the same order as [`InitReport::hooks`].
+    /// This is called by `init_detours` for a `#[hook_module("...", transactional)]`
+    ///
+    /// # Safety
+    /// Same as [`retour::StaticDetour::disable`]
+    pub unsafe fn roll_back_on_failure(
+        &mut self,
+        disable_fns: &[unsafe fn() -> retour::Result<()>],
+    ) {
+        if self.is_ok() {
+            return;
+        }
+        for (hook, disable_fn) in self.hooks.iter_mut().zip(disable_fns) {
+            if hook.is_ok() {
+                hook.outcome = match disable_fn() {
+                    Ok(()) => Err(Error::RolledBack),
+                    Err(e) => Err(e.into()),
+                };
+            }
+        }
+    }
+
+    /// `Ok(self)` if every hook was initialized, otherwise [`Error::InitFailed`] holding this report
+    pub fn into_result(self) -> Result<Self> {
+        if self.is_ok() {
+            Ok(self)
+        } else {
+            Err(Error::InitFailed(self))
+        }
+    }
+}
+
+impl Display for InitReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for hook in &self.hooks {
+            writeln!(f, "{hook}")?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,35 @@
+use retour_utils::hook_module;
+
+#[derive(Clone, Copy)]
+pub struct Point {
+    x: i32,
+    y: i32,
+}
+
+#[hook_module("foo.bar")]
+mod hooks {
+    use super::Point;
+
+    #[hook(WildcardHook, symbol = "Wildcard")]
+    fn wildcard_hook(_: i32, b: i32) -> i32 {
+        WildcardHook.call(0, b)
+    }
+
+    #[hook(MutHook, symbol = "Mut")]
+    fn mut_hook(mut a: i32, b: i32) -> i32 {
+        a += 1;
+        MutHook.call(a, b)
+    }
+
+    #[hook(StructHook, symbol = "Struct")]
+    fn struct_hook(Point { x, y }: Point) -> i32 {
+        StructHook.call(Point { x: y, y: x })
+    }
+
+    #[hook(TupleHook, symbol = "Tuple")]
+    fn tuple_hook((a, _): (i32, i32), [first, ..]: [u8; 4]) -> i32 {
+        TupleHook.call((a, a), [first; 4])
+    }
+}
+
+fn main() {}
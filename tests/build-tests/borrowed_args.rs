@@ -0,0 +1,99 @@
+use retour_utils::{hook_module, LookupData, ModuleLoad};
+
+// Borrows in a hook's signature are passed as pointers to the hooked function
+
+#[cfg(windows)]
+#[hook_module("kernel32.dll")]
+mod hooks {
+    #[repr(C)]
+    #[derive(Default)]
+    pub struct SystemTime {
+        pub year: u16,
+        month: u16,
+        day_of_week: u16,
+        day: u16,
+        hour: u16,
+        minute: u16,
+        second: u16,
+        milliseconds: u16,
+    }
+
+    #[hook(unsafe extern "system" GetSystemTimeHook, symbol = "GetSystemTime")]
+    fn get_system_time<'a>(time: &'a mut SystemTime) {
+        unsafe { GetSystemTimeHook.call(time) };
+        time.year = 1999;
+    }
+}
+
+#[cfg(not(windows))]
+#[hook_module("libc.so.6")]
+mod hooks {
+    use std::ffi::{c_char, c_int, c_long};
+
+    #[repr(C)]
+    #[derive(Debug)]
+    pub struct Tm {
+        second: c_int,
+        minute: c_int,
+        hour: c_int,
+        day: c_int,
+        month: c_int,
+        pub year: c_int,
+        day_of_week: c_int,
+        day_of_year: c_int,
+        is_dst: c_int,
+        gmt_offset: c_long,
+        zone: *const c_char,
+    }
+
+    impl Default for Tm {
+        fn default() -> Self {
+            unsafe { std::mem::zeroed() }
+        }
+    }
+
+    #[hook(unsafe extern "C" GmTimeHook, symbol = "gmtime_r")]
+    fn gmtime<'a>(time: &i64, tm: &'a mut Tm) -> Option<&'a mut Tm> {
+        let tm = unsafe { GmTimeHook.call(time, tm).as_mut() }?;
+        tm.year = 99;
+        Some(tm)
+    }
+
+    pub type GmTime = unsafe extern "C" fn(*const i64, *mut Tm) -> *mut Tm;
+}
+
+#[cfg(windows)]
+fn main() {
+    unsafe {
+        hooks::init_detours().unwrap();
+
+        let addr = LookupData::from_symbol(hooks::MODULE_NAME, "GetSystemTime")
+            .resolve(ModuleLoad::Attach)
+            .unwrap();
+        let get_system_time: unsafe extern "system" fn(*mut hooks::SystemTime) =
+            std::mem::transmute(addr);
+        let mut time = hooks::SystemTime::default();
+        get_system_time(&mut time);
+        assert_eq!(time.year, 1999);
+
+        hooks::disable_detours().unwrap();
+    }
+}
+
+#[cfg(not(windows))]
+fn main() {
+    unsafe {
+        hooks::init_detours().unwrap();
+
+        let addr = LookupData::from_symbol(hooks::MODULE_NAME, "gmtime_r")
+            .resolve(ModuleLoad::Attach)
+            .unwrap();
+        let gmtime: hooks::GmTime = std::mem::transmute(addr);
+        let mut tm = hooks::Tm::default();
+        let result = gmtime(&0, &mut tm);
+        assert_eq!(result, &mut tm as *mut hooks::Tm);
+        assert_eq!(tm.year, 99);
+
+        hooks::disable_detours().unwrap();
+    }
+}
@@ -0,0 +1,70 @@
+use retour_utils::{hook_module, Error};
+
+#[hook_module("foo.dll", transactional)]
+mod hooks {
+    // Doesn't exist, but neither does anything generated for this hook
+    #[cfg(any())]
+    #[hook(symbol = "Removed", group = "render")]
+    fn removed() {
+        missing_function()
+    }
+
+    /// Documents `present_Detour` too
+    #[hook(symbol = "Present", group = "render")]
+    fn present() {
+        present_Detour.call()
+    }
+
+    // One of these is compiled on each platform, so they can share the detour name and target
+    #[cfg(windows)]
+    #[hook(PlatformHook, symbol = "Platform")]
+    fn platform_hook() -> u32 {
+        PlatformHook.call()
+    }
+
+    #[cfg(not(windows))]
+    #[hook(PlatformHook, symbol = "Platform")]
+    fn platform_hook() -> i32 {
+        PlatformHook.call()
+    }
+
+    #[cfg_attr(all(), cfg(any()))]
+    #[hook(symbol = "AlsoRemoved")]
+    fn also_removed() {
+        missing_function()
+    }
+
+    pub struct Foo;
+
+    #[cfg(any())]
+    impl Foo {
+        #[hook(unsafe extern "C", symbol = "RemovedMethod")]
+        fn removed_method(&self) {
+            missing_function()
+        }
+    }
+
+    #[hook(symbol = "Last", group = "render")]
+    fn last() {
+        last_Detour.call()
+    }
+}
+
+fn main() {
+    let detour_names: Vec<_> = hooks::HOOKS.iter().map(|hook| hook.detour_name).collect();
+    assert_eq!(detour_names, ["present_Detour", "PlatformHook", "last_Detour"]);
+
+    // Each hook is initialized with its own descriptor
+    let Err(Error::InitFailed(report)) = (unsafe { hooks::init_detours() }) else {
+        panic!("foo.dll isn't loaded");
+    };
+    let detour_names: Vec<_> = report.hooks.iter().map(|hook| hook.detour_name).collect();
+    assert_eq!(detour_names, ["present_Detour", "PlatformHook", "last_Detour"]);
+
+    let Err(Error::InitFailed(report)) = (unsafe { hooks::init_group("render") }) else {
+        panic!("foo.dll isn't loaded");
+    };
+    let detour_names: Vec<_> = report.hooks.iter().map(|hook| hook.detour_name).collect();
+    assert_eq!(detour_names, ["present_Detour", "last_Detour"]);
+    assert!(!hooks::is_enabled());
+}
@@ -0,0 +1,78 @@
+use std::sync::{
+    atomic::{AtomicU32, Ordering},
+    Arc,
+};
+
+use retour_utils::{hook_module, install_closure, Error, LookupData};
+
+#[path = "common/process.rs"]
+#[macro_use]
+mod process;
+
+macro_rules! define_hooks {
+    ($lib:literal, $process_id:literal, $thread_id:literal) => {
+        #[hook_module($lib)]
+        mod hooks {
+            use crate::process::Id;
+
+            // `init_detours` isn't called, the closures below are installed instead
+            #[hook(pub unsafe extern "system" ProcessIdHook, symbol = $process_id)]
+            fn process_id_hook() -> Id {
+                unsafe { ProcessIdHook.call() }
+            }
+        }
+    };
+}
+
+with_process_lib!(define_hooks);
+
+fn main() {
+    let real_id = process::process_id();
+    let calls = Arc::new(AtomicU32::new(0));
+
+    let counter = calls.clone();
+    unsafe {
+        install_closure(
+            &hooks::ProcessIdHook,
+            LookupData::from_symbol(hooks::MODULE_NAME, process::PROCESS_ID),
+            move || {
+                counter.fetch_add(1, Ordering::Relaxed);
+                hooks::ProcessIdHook.call()
+            },
+        )
+    }
+    .unwrap();
+    assert_eq!(process::process_id(), real_id);
+    assert_eq!(process::process_id(), real_id);
+    assert_eq!(calls.load(Ordering::Relaxed), 2);
+    // `HOOKS` doesn't know about the closure
+    assert_eq!(hooks::HOOKS[0].address(), None);
+
+    // Replaced while the hook stays installed
+    hooks::ProcessIdHook.set_detour(|| 42);
+    assert!(hooks::ProcessIdHook.is_enabled());
+    assert_eq!(process::process_id(), 42);
+    assert_eq!(calls.load(Ordering::Relaxed), 2);
+
+    // It's initialized already
+    let reinstall = unsafe {
+        install_closure(
+            &hooks::ProcessIdHook,
+            LookupData::from_symbol(hooks::MODULE_NAME, process::PROCESS_ID),
+            || 0,
+        )
+    };
+    assert!(reinstall.is_err());
+
+    // `init_detours` can't initialize it either
+    let Err(Error::InitFailed(report)) = (unsafe { hooks::init_detours() }) else {
+        panic!("the hook was initialized by install_closure");
+    };
+    assert!(matches!(
+        report.hooks[0].outcome,
+        Err(Error::DetourError(retour::Error::AlreadyInitialized))
+    ));
+
+    unsafe { hooks::disable_detours() }.unwrap();
+    assert_eq!(process::process_id(), real_id);
+}
@@ -0,0 +1,53 @@
+//! Library every test process has loaded, for the tests that install hooks:
+//! ```ignore
+//! #[path = "common/process.rs"]
+//! #[macro_use]
+//! mod process;
+//! ```
+//! `#[hook_module]` and `#[hook]` only take string literals, so `with_process_lib!(callback)` expands to
+//! `callback!("LIBRARY", "PROCESS_ID_SYMBOL", "THREAD_ID_SYMBOL")`. Both functions take no arguments and
+//! return [`Id`], with the `"system"` ABI (the C ABI outside of Windows)
+
+// Each test only uses part of it
+#![allow(dead_code, unused_macros)]
+
+#[cfg(windows)]
+pub type Id = u32;
+#[cfg(not(windows))]
+pub type Id = i32;
+
+#[cfg(windows)]
+macro_rules! with_process_lib {
+    ($callback:ident) => {
+        $callback!("kernel32.dll", "GetCurrentProcessId", "GetCurrentThreadId");
+    };
+}
+
+#[cfg(all(target_os = "linux", target_env = "gnu"))]
+macro_rules! with_process_lib {
+    ($callback:ident) => {
+        $callback!("libc.so.6", "getpid", "gettid");
+    };
+}
+
+macro_rules! process_lib_items {
+    ($lib:literal, $process_id:literal, $thread_id:literal) => {
+        pub const LIB: &str = $lib;
+        pub const PROCESS_ID: &str = $process_id;
+        pub const THREAD_ID: &str = $thread_id;
+
+        extern "system" {
+            #[link_name = $process_id]
+            fn raw_process_id() -> Id;
+        }
+    };
+}
+
+with_process_lib!(process_lib_items);
+
+pub type ProcessIdFn = unsafe extern "system" fn() -> Id;
+
+/// Calls the process id function through the library's export, so hooks on it apply
+pub fn process_id() -> Id {
+    unsafe { raw_process_id() }
+}
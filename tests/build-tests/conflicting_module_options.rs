@@ -0,0 +1,11 @@
+use retour_utils::hook_module;
+
+#[hook_module("foo.dll", load_if_missing, deferred)]
+mod lua {
+    #[hook(DtLuaLoad, offset = 0x1234)]
+    fn lua_load() {
+
+    }
+}
+// needed for trybuild
+fn main() {}
@@ -0,0 +1,76 @@
+use std::{
+    sync::atomic::{AtomicBool, Ordering},
+    thread,
+    time::{Duration, Instant},
+};
+
+use retour_utils::{deferred_status, hook_module, DeferredStatus, ModuleLoad};
+
+// Modules the test binary doesn't link, so they're only loaded once `main` loads them
+
+#[cfg(windows)]
+#[hook_module("winmm.dll", deferred)]
+mod late {
+    #[hook(unsafe extern "system" TimeGetTimeHook, symbol = "timeGetTime")]
+    fn time_get_time_hook() -> u32 {
+        42
+    }
+
+    pub unsafe fn is_hooked(addr: *const ()) -> bool {
+        let time_get_time: unsafe extern "system" fn() -> u32 = std::mem::transmute(addr);
+        time_get_time() == 42
+    }
+}
+
+#[cfg(all(target_os = "linux", target_env = "gnu"))]
+#[hook_module("libm.so.6", deferred)]
+mod late {
+    #[hook(unsafe extern "C" CosHook, symbol = "cos")]
+    fn cos_hook(_: f64) -> f64 {
+        42.0
+    }
+
+    pub unsafe fn is_hooked(addr: *const ()) -> bool {
+        let cos: unsafe extern "C" fn(f64) -> f64 = std::mem::transmute(addr);
+        cos(0.0) == 42.0
+    }
+}
+
+static INSTALLED: AtomicBool = AtomicBool::new(false);
+
+#[cfg(any(windows, all(target_os = "linux", target_env = "gnu")))]
+fn main() {
+    retour_utils::on_deferred_install(|module, result| {
+        assert_eq!(module, late::MODULE_NAME);
+        assert!(result.is_ok());
+        INSTALLED.store(true, Ordering::SeqCst);
+    });
+
+    assert!(unsafe { late::init_detours() }.is_ok());
+    assert_eq!(
+        deferred_status(late::MODULE_NAME),
+        Some(DeferredStatus::Pending)
+    );
+
+    // Loading the module lets the watcher thread install the hooks
+    let addr = late::HOOKS[0]
+        .lookup
+        .resolve(ModuleLoad::LoadIfMissing)
+        .unwrap();
+    let start = Instant::now();
+    while !INSTALLED.load(Ordering::SeqCst) {
+        assert!(
+            start.elapsed() < Duration::from_secs(10),
+            "deferred hooks weren't installed"
+        );
+        thread::sleep(Duration::from_millis(10));
+    }
+    assert_eq!(
+        deferred_status(late::MODULE_NAME),
+        Some(DeferredStatus::Installed)
+    );
+    assert!(unsafe { late::is_hooked(addr) });
+}
+
+#[cfg(not(any(windows, all(target_os = "linux", target_env = "gnu"))))]
+fn main() {}
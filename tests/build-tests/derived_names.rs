@@ -0,0 +1,83 @@
+use retour_utils::hook_module;
+
+#[path = "common/process.rs"]
+#[macro_use]
+mod process;
+
+macro_rules! define_hooks {
+    ($lib:literal, $process_id:literal, $thread_id:literal) => {
+        #[hook_module($lib)]
+        mod hooks {
+            use crate::process::Id;
+
+            // Creates `process_id_Detour`
+            #[hook(unsafe extern "system", symbol = $process_id)]
+            fn process_id() -> Id {
+                unsafe { process_id_Detour.call() }
+            }
+
+            // Hooks the `retour_utils_missing_symbol` symbol
+            #[hook(unsafe extern "system", optional)]
+            fn retour_utils_missing_symbol() {}
+        }
+    };
+}
+
+with_process_lib!(define_hooks);
+
+#[hook_module("foo.dll", detour_names = "Hook_{}")]
+mod named_hooks {
+    #[hook(symbol = "Foo")]
+    fn foo() {
+        Hook_foo.call()
+    }
+
+    #[hook]
+    fn r#type() {
+        Hook_type.call()
+    }
+
+    #[hook(ExplicitName)]
+    fn bar() {
+        ExplicitName.call()
+    }
+
+    // Options without a value aren't taken for a detour name
+    #[hook(optional)]
+    fn baz() {
+        Hook_baz.call()
+    }
+
+    #[hook(optional, group = "extra")]
+    fn qux() {
+        Hook_qux.call()
+    }
+}
+
+fn main() {
+    let report = unsafe { hooks::init_detours() }.unwrap();
+    let skipped: Vec<_> = report.skipped().map(|hook| hook.detour_name).collect();
+    assert_eq!(skipped, ["retour_utils_missing_symbol_Detour"]);
+    assert_eq!(report.hooks[0].detour_name, "process_id_Detour");
+    assert_eq!(
+        report.hooks[1].lookup.to_string(),
+        format!("{}!retour_utils_missing_symbol", process::LIB)
+    );
+    unsafe { hooks::disable_detours() }.unwrap();
+
+    let hooks: Vec<_> = named_hooks::HOOKS
+        .iter()
+        .map(|hook| (hook.detour_name, hook.lookup.to_string(), hook.optional))
+        .collect();
+    assert_eq!(
+        hooks,
+        [
+            ("Hook_foo", "foo.dll!Foo".to_string(), false),
+            ("Hook_type", "foo.dll!type".to_string(), false),
+            ("ExplicitName", "foo.dll!bar".to_string(), false),
+            ("Hook_baz", "foo.dll!baz".to_string(), true),
+            ("Hook_qux", "foo.dll!qux".to_string(), true),
+        ]
+    );
+    assert_eq!(named_hooks::HOOKS[4].group, Some("extra"));
+}
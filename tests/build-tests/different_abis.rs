@@ -3,54 +3,68 @@ use retour_utils::hook_module;
 
 #[hook_module("foo.bar")]
 mod hooks {
-    #[hook(NormalHook, symbol = "Foo")]
+    #[hook(NormalHook, symbol = "Normal")]
     fn normal_hook() -> i32 {
         NormalHook.call()
     }
 
-    #[hook(unsafe UnsafeHook, symbol = "Foo")]
+    #[hook(unsafe UnsafeHook, symbol = "Unsafe")]
     fn unsafe_hook() -> i32 {
         unsafe {
             UnsafeHook.call()
         }
     }
 
-    #[hook(extern "cdecl" CDeclAbiHook, symbol = "Foo")]
+    #[hook(extern "cdecl" CDeclAbiHook, symbol = "CDecl")]
     fn cdecl_abi_hook() -> i32 {
         CDeclAbiHook.call()
     }
 
-    #[hook(extern "stdcall" StdCallAbiHook, symbol = "Foo")]
+    #[hook(extern "stdcall" StdCallAbiHook, symbol = "StdCall")]
     fn stdcall_abi_hook() -> i32 {
         StdCallAbiHook.call()
     }
 
-    #[hook(extern "fastcall" FastCallAbiHook, symbol = "Foo")]
+    #[hook(extern "fastcall" FastCallAbiHook, symbol = "FastCall")]
     fn fastcall_abi_hook() -> i32 {
         FastCallAbiHook.call()
     }
 
-    #[hook(extern "C" CAbiHook, symbol = "Foo")]
+    #[hook(extern "C" CAbiHook, symbol = "CAbi")]
     fn c_abi_hook() -> i32 {
         CAbiHook.call()
     }
 
-    #[hook(extern "system" SystemAbiHook, symbol = "Foo")]
+    #[hook(extern "system" SystemAbiHook, symbol = "SystemAbi")]
     fn system_abi_hook() -> i32 {
         SystemAbiHook.call()
     }
 
+}
+#[cfg(all(feature = "thiscall-abi", target_arch = "x86"))]
+#[hook_module("foo.bar")]
+mod thiscall {
+    #[hook(extern "thiscall" ThisCallHook, symbol = "ThisCall")]
+    fn thiscall_abi_hook() -> i32 {
+        ThisCallHook.call()
+    }
 
-    // #[hook(extern "thiscall" ThisCallHook, symbol = "Foo")]
-    // fn thiscall_abi_hook() -> i32 {
-    //     ThisCallHook.call()
-    // }
+    #[repr(C)]
+    pub struct Foo {
+        bar: i32,
+    }
 
+    impl Foo {
+        #[hook(unsafe extern "thiscall" ThisCallMethodHook, symbol = "ThisCallMethod")]
+        fn thiscall_method_hook(&mut self, bar: i32) -> i32 {
+            unsafe { ThisCallMethodHook.call(self, bar) }
+        }
+    }
 }
 #[cfg(all(target_family = "windows", target_arch = "x86_64"))]
 #[hook_module("foo.bar")]
 mod win64 {
-    #[hook(extern "win64" Win64AbiHook, symbol = "Foo")]
+    #[hook(extern "win64" Win64AbiHook, symbol = "Win64Abi")]
     fn win64_abi_hook() -> i32 {
         Win64AbiHook.call()
     }
@@ -0,0 +1,27 @@
+use retour_utils::hook_module;
+
+#[hook_module("foo.dll")]
+mod hooks {
+    #[hook(FooHook, symbol = "Foo")]
+    fn foo_hook() {}
+
+    #[hook(FooHook, symbol = "Bar")]
+    fn same_name_hook() {}
+
+    #[hook(OtherFooHook, symbol = "Foo")]
+    fn same_symbol_hook() {}
+
+    #[hook(OffsetHook, offset = 0x10)]
+    fn offset_hook() {}
+
+    #[hook(OtherOffsetHook, offset = 16)]
+    fn same_offset_hook() {}
+
+    #[hook(PatternHook, pattern = "48 8b ? 57")]
+    fn pattern_hook() {}
+
+    #[hook(OtherPatternHook, pattern = "48 8B ?? 57")]
+    fn same_pattern_hook() {}
+}
+
+fn main() {}
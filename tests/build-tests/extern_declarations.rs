@@ -0,0 +1,84 @@
+use retour_utils::hook_module;
+
+#[path = "common/process.rs"]
+#[macro_use]
+mod process;
+
+macro_rules! define_hooks {
+    ($lib:literal, $process_id:literal, $thread_id:literal) => {
+        #[hook_module($lib)]
+        mod hooks {
+            use crate::process::Id;
+
+            extern "system" {
+                #[link_name = $process_id]
+                fn current_process_id() -> Id;
+                #[link_name = $thread_id]
+                fn current_thread_id() -> Id;
+            }
+
+            // Creates `current_process_id_Detour`, hooking the declaration's `link_name`
+            #[hook(current_process_id)]
+            fn process_id() -> Id {
+                unsafe { current_process_id_Detour.call() }
+            }
+
+            #[hook(current_thread_id)]
+            fn thread_id() -> Id {
+                unsafe { current_thread_id_Detour.call() }
+            }
+        }
+    };
+}
+
+with_process_lib!(define_hooks);
+
+#[hook_module("lua52.dll")]
+mod lua {
+    use std::ffi::c_int;
+
+    #[repr(C)]
+    pub struct LuaState {
+        _private: [u8; 0],
+    }
+
+    extern "C" {
+        fn lua_settop(l: *mut LuaState, idx: c_int);
+    }
+
+    // Creates `lua_settop_Detour`, hooking the `lua_settop` symbol
+    #[hook(lua_settop)]
+    fn settop(l: *mut LuaState, idx: c_int) {
+        unsafe { lua_settop_Detour.call(l, idx) }
+    }
+}
+
+fn main() {
+    unsafe { hooks::init_detours() }.unwrap();
+    let hooks: Vec<_> = hooks::HOOKS
+        .iter()
+        .map(|hook| (hook.detour_name, hook.fn_name, hook.lookup.to_string()))
+        .collect();
+    assert_eq!(
+        hooks,
+        [
+            (
+                "current_process_id_Detour",
+                "process_id",
+                format!("{}!{}", process::LIB, process::PROCESS_ID)
+            ),
+            (
+                "current_thread_id_Detour",
+                "thread_id",
+                format!("{}!{}", process::LIB, process::THREAD_ID)
+            ),
+        ]
+    );
+    assert!(hooks::HOOKS.iter().all(|hook| hook.abi == Some("system")));
+    unsafe { hooks::disable_detours() }.unwrap();
+
+    let settop = &lua::HOOKS[0];
+    assert_eq!(settop.detour_name, "lua_settop_Detour");
+    assert_eq!(settop.lookup.to_string(), "lua52.dll!lua_settop");
+    assert_eq!(settop.abi, Some("C"));
+}
@@ -0,0 +1,49 @@
+use retour_utils::hook_module;
+
+#[path = "common/process.rs"]
+#[macro_use]
+mod process;
+
+macro_rules! define_hooks {
+    ($lib:literal, $process_id:literal, $thread_id:literal) => {
+        #[hook_module($lib)]
+        mod hooks {
+            use std::sync::atomic::{AtomicUsize, Ordering};
+
+            use crate::process::{Id, ProcessIdFn};
+            use retour_utils::HookCtx;
+
+            pub static RETURN_ADDRESS: AtomicUsize = AtomicUsize::new(0);
+
+            #[hook(pub unsafe extern "system" ProcessIdHook, symbol = $process_id)]
+            fn process_id_hook(ctx: &HookCtx<ProcessIdFn>) -> Id {
+                assert_eq!(ctx.hook().detour_name, "ProcessIdHook");
+                RETURN_ADDRESS.store(ctx.return_address(), Ordering::Relaxed);
+                let process_id = unsafe { ctx.call() };
+                process_id + ctx.call_number() as Id
+            }
+        }
+    };
+}
+
+with_process_lib!(define_hooks);
+
+fn main() {
+    let process_id = process::process_id();
+    unsafe {
+        hooks::init_detours().unwrap();
+    }
+    // The context isn't part of the hooked function's signature
+    let original = unsafe { hooks::ProcessIdHook.call() };
+    assert_eq!(original, process_id);
+    assert_eq!(process::process_id(), process_id + 1);
+    assert_eq!(process::process_id(), process_id + 2);
+    assert_eq!(hooks::HOOKS[0].calls(), 2);
+    // `process::process_id` is what calls the hooked function
+    let caller = process::process_id as *const () as usize;
+    let return_address = hooks::RETURN_ADDRESS.load(std::sync::atomic::Ordering::Relaxed);
+    assert!((caller..caller + 64).contains(&return_address));
+    unsafe {
+        hooks::disable_detours().unwrap();
+    }
+}
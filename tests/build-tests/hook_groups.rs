@@ -0,0 +1,49 @@
+use retour_utils::{hook_module, Error};
+
+#[path = "common/process.rs"]
+#[macro_use]
+mod process;
+
+macro_rules! define_hooks {
+    ($lib:literal, $process_id:literal, $thread_id:literal) => {
+        #[hook_module($lib)]
+        mod hooks {
+            use crate::process::Id;
+
+            #[hook(pub unsafe extern "system" ProcessIdHook, symbol = $process_id, group = "pid")]
+            fn process_id_hook() -> Id {
+                unsafe { ProcessIdHook.call() }
+            }
+
+            #[hook(pub unsafe extern "system" ThreadIdHook, symbol = $thread_id, group = "tid")]
+            fn thread_id_hook() -> Id {
+                unsafe { ThreadIdHook.call() }
+            }
+        }
+    };
+}
+
+with_process_lib!(define_hooks);
+
+fn main() {
+    unsafe {
+        // Only the hooks of the group are initialized
+        let report = hooks::init_group("pid").unwrap();
+        assert_eq!(report.hooks.len(), 1);
+        assert!(hooks::ProcessIdHook.is_enabled());
+        assert!(!hooks::ThreadIdHook.is_enabled());
+
+        hooks::init_group("tid").unwrap();
+        hooks::disable_group("pid").unwrap();
+        assert!(!hooks::ProcessIdHook.is_enabled());
+        assert!(hooks::ThreadIdHook.is_enabled());
+        hooks::enable_group("pid").unwrap();
+        assert!(hooks::ProcessIdHook.is_enabled());
+
+        assert!(matches!(
+            hooks::enable_group("render"),
+            Err(Error::UnknownGroup)
+        ));
+        hooks::disable_detours().unwrap();
+    }
+}
@@ -0,0 +1,54 @@
+use retour_utils::{hook_module, HookModule};
+
+#[path = "common/process.rs"]
+#[macro_use]
+mod process;
+
+macro_rules! define_hooks {
+    ($lib:literal, $process_id:literal, $thread_id:literal) => {
+        #[hook_module($lib)]
+        mod process_hooks {
+            use crate::process::Id;
+
+            #[hook(unsafe extern "system" ProcessIdHook, symbol = $process_id)]
+            fn process_id_hook() -> Id {
+                unsafe { ProcessIdHook.call() }
+            }
+        }
+    };
+}
+
+with_process_lib!(define_hooks);
+
+#[hook_module("foo.dll")]
+mod missing_hooks {
+    #[hook(MissingHook, symbol = "Foo")]
+    fn missing_hook() {
+        MissingHook.call()
+    }
+}
+
+fn toggle<M: HookModule>(module: M) {
+    unsafe {
+        module.disable().unwrap();
+        assert!(!module.is_enabled());
+        module.enable().unwrap();
+        assert!(module.is_enabled());
+    }
+}
+
+fn main() {
+    let modules: Vec<&dyn HookModule> = vec![&process_hooks::Module, &missing_hooks::Module];
+    let names: Vec<_> = modules.iter().map(|module| module.module_name()).collect();
+    assert_eq!(names, [process_hooks::MODULE_NAME, "foo.dll"]);
+
+    let results: Vec<_> = modules
+        .iter()
+        .map(|module| unsafe { module.init() }.is_ok())
+        .collect();
+    assert_eq!(results, [true, false]);
+    assert_eq!(missing_hooks::Module.hooks()[0].detour_name, "MissingHook");
+
+    toggle(process_hooks::Module);
+    unsafe { process_hooks::Module.disable() }.unwrap();
+}
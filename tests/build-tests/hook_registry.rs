@@ -0,0 +1,72 @@
+use retour_utils::{hook_module, hook_modules, LookupData};
+
+#[path = "common/process.rs"]
+#[macro_use]
+mod process;
+
+macro_rules! define_hooks {
+    ($lib:literal, $process_id:literal, $thread_id:literal) => {
+        #[hook_module($lib)]
+        mod hooks {
+            use crate::process::Id;
+
+            #[hook(unsafe extern "system" ProcessIdHook, symbol = $process_id, group = "pid")]
+            fn process_id_hook() -> Id {
+                unsafe { ProcessIdHook.call() }
+            }
+        }
+    };
+}
+
+with_process_lib!(define_hooks);
+
+#[hook_module("foo.dll")]
+mod other_hooks {
+    #[hook(OffsetHook, offset = 0x1234, optional)]
+    fn offset_hook() {
+        OffsetHook.call()
+    }
+}
+
+fn main() {
+    let hook = &hooks::HOOKS[0];
+    assert_eq!(hook.detour_name, "ProcessIdHook");
+    assert_eq!(hook.fn_name, "process_id_hook");
+    assert_eq!(hook.abi, Some("system"));
+    assert_eq!(hook.lookup.get_module(), hooks::MODULE_NAME);
+    assert_eq!(hook.group, Some("pid"));
+    assert!(!hook.optional);
+    assert_eq!(hook.address(), None);
+    assert!(!hook.is_enabled());
+
+    unsafe { hooks::init_detours() }.unwrap();
+    assert!(hook.address().is_some());
+    assert!(hook.is_enabled());
+    unsafe { hooks::disable_detours() }.unwrap();
+    assert!(!hook.is_enabled());
+
+    let other_hook = &other_hooks::HOOKS[0];
+    assert_eq!(other_hook.abi, None);
+    assert_eq!(
+        other_hook.lookup,
+        LookupData::from_offset("foo.dll", 0x1234)
+    );
+    assert!(other_hook.optional);
+
+    // Every hook module is registered, whether or not it was initialized
+    let mut modules: Vec<_> = hook_modules()
+        .iter()
+        .map(|module| {
+            let mod_name = module.path.rsplit("::").next().unwrap();
+            (mod_name, module.name, module.hooks.len())
+        })
+        .collect();
+    modules.sort();
+    assert_eq!(
+        modules,
+        [
+            ("hooks", hooks::MODULE_NAME, 1),
+            ("other_hooks", "foo.dll", 1),
+        ]
+    );
+}
@@ -0,0 +1,51 @@
+use retour_utils::{Error, HookSet, LookupData};
+
+#[path = "common/process.rs"]
+mod process;
+
+use process::{Id, ProcessIdFn};
+
+unsafe extern "system" fn process_id_hook() -> Id {
+    42
+}
+
+fn main() {
+    let real_id = process::process_id();
+    // Names read at runtime, e.g. from a config file
+    let symbol: &'static str = String::from(process::PROCESS_ID).leak();
+    let mut hooks = HookSet::new()
+        .hook::<ProcessIdFn>(
+            "process_id",
+            LookupData::from_symbol(process::LIB, symbol),
+            process_id_hook,
+        )
+        .raw(
+            "missing",
+            LookupData::from_symbol(process::LIB, "retour_utils_missing_symbol"),
+            process_id_hook as *const (),
+        );
+
+    let Err(Error::InitFailed(report)) = (unsafe { hooks.init() }) else {
+        panic!("the missing symbol should fail");
+    };
+    let failures: Vec<_> = report.failures().map(|hook| hook.detour_name).collect();
+    assert_eq!(failures, ["missing"]);
+    assert!(hooks.is_enabled());
+    assert_eq!(process::process_id(), 42);
+
+    // The original function is only given for the type the hook was added with
+    let original = hooks.original::<ProcessIdFn>("process_id").unwrap();
+    assert_eq!(unsafe { original() }, real_id);
+    assert!(hooks.original::<fn() -> Id>("process_id").is_none());
+    assert!(hooks.trampoline("missing").is_none());
+
+    unsafe { hooks.disable() }.unwrap();
+    assert!(!hooks.is_enabled());
+    assert_eq!(process::process_id(), real_id);
+    unsafe { hooks.enable() }.unwrap();
+    assert_eq!(process::process_id(), 42);
+
+    // Dropping the set removes its hooks
+    drop(hooks);
+    assert_eq!(process::process_id(), real_id);
+}
@@ -0,0 +1,36 @@
+use retour_utils::{hook_module, Error};
+
+#[path = "common/process.rs"]
+#[macro_use]
+mod process;
+
+macro_rules! define_hooks {
+    ($lib:literal, $process_id:literal, $thread_id:literal) => {
+        #[hook_module($lib)]
+        mod hooks {
+            #[hook(MissingHook, symbol = "retour_utils_missing_symbol")]
+            fn missing_hook() {
+                MissingHook.call()
+            }
+
+            #[hook(OtherMissingHook, symbol = "retour_utils_other_missing_symbol")]
+            fn other_missing_hook() {
+                OtherMissingHook.call()
+            }
+        }
+    };
+}
+
+with_process_lib!(define_hooks);
+
+fn main() {
+    // Every failing hook is reported, not just the first one
+    let Err(Error::InitFailed(report)) = (unsafe { hooks::init_detours() }) else {
+        panic!("missing symbols should fail");
+    };
+    let failed: Vec<_> = report.failures().map(|hook| hook.detour_name).collect();
+    assert_eq!(failed, ["MissingHook", "OtherMissingHook"]);
+    assert!(report
+        .failures()
+        .all(|hook| hook.address.is_none() && matches!(hook.outcome, Err(Error::SymbolNotFound))));
+}
@@ -12,7 +12,7 @@ mod baz {
 
     mod other_scope {
         pub unsafe fn bar() {
-            super::pub_scope::init_detours().unwrap()
+            super::pub_scope::init_detours().unwrap();
         }
     }
 }
@@ -0,0 +1,24 @@
+use retour_utils::hook_module;
+
+#[hook_module("foo.dll")]
+mod hooks {
+    #[hook(MissingComma symbol = "Foo")]
+    fn missing_comma() {}
+
+    #[hook(WrongSymbolType, symbol = 0x1234)]
+    fn wrong_symbol_type() {}
+
+    #[hook(UnknownOption, symbol = "Foo", priority = 1)]
+    fn unknown_option() {}
+
+    #[hook(InvalidPattern, pattern = "48 8B XY")]
+    fn invalid_pattern() {}
+
+    #[hook(FollowPastPattern, pattern = "E8 ?? ?? ?? ??", follow = rel32(40))]
+    fn follow_past_pattern() {}
+}
+
+#[retour_utils::hook(MissingModule, symbol = "Foo")]
+fn missing_module() {}
+
+fn main() {}
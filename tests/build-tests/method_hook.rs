@@ -0,0 +1,82 @@
+use retour_utils::{hook_module, LookupData, ModuleLoad};
+
+// Hooks written as methods get the first argument of the hooked function as their receiver
+
+#[cfg(windows)]
+#[hook_module("kernel32.dll")]
+mod hooks {
+    #[repr(C)]
+    #[derive(Default)]
+    pub struct SystemTime {
+        pub year: u16,
+        month: u16,
+        day_of_week: u16,
+        day: u16,
+        hour: u16,
+        minute: u16,
+        second: u16,
+        milliseconds: u16,
+    }
+
+    impl SystemTime {
+        #[hook(unsafe extern "system" GetSystemTimeHook, symbol = "GetSystemTime")]
+        fn get_system_time(&mut self) {
+            unsafe { GetSystemTimeHook.call(self) };
+            self.year = 1999;
+        }
+    }
+
+    pub const SYMBOL: &str = "GetSystemTime";
+    pub type Hooked = unsafe extern "system" fn(*mut SystemTime);
+    pub type Receiver = SystemTime;
+    pub const FN_NAME: &str = "SystemTime::get_system_time";
+
+    pub fn marker(time: &SystemTime) -> u64 {
+        time.year as u64
+    }
+}
+
+#[cfg(not(windows))]
+#[hook_module("libc.so.6")]
+mod hooks {
+    #[repr(C)]
+    #[derive(Default)]
+    pub struct SignalSet {
+        bits: [u64; 16],
+    }
+
+    impl SignalSet {
+        #[hook(unsafe extern "C" EmptySignalSetHook, symbol = "sigemptyset")]
+        fn empty(&mut self) -> i32 {
+            let result = unsafe { EmptySignalSetHook.call(self) };
+            self.bits[0] = 1999;
+            result
+        }
+    }
+
+    pub const SYMBOL: &str = "sigemptyset";
+    pub type Hooked = unsafe extern "C" fn(*mut SignalSet) -> i32;
+    pub type Receiver = SignalSet;
+    pub const FN_NAME: &str = "SignalSet::empty";
+
+    pub fn marker(set: &SignalSet) -> u64 {
+        set.bits[0]
+    }
+}
+
+fn main() {
+    unsafe {
+        hooks::init_detours().unwrap();
+        assert_eq!(hooks::HOOKS[0].fn_name, hooks::FN_NAME);
+
+        let addr = LookupData::from_symbol(hooks::MODULE_NAME, hooks::SYMBOL)
+            .resolve(ModuleLoad::Attach)
+            .unwrap();
+        let hooked: hooks::Hooked = std::mem::transmute(addr);
+        let mut receiver = hooks::Receiver::default();
+        hooked(&mut receiver);
+        assert_eq!(hooks::marker(&receiver), 1999);
+
+        hooks::disable_detours().unwrap();
+    }
+}
@@ -0,0 +1,43 @@
+use retour_utils::{hook_module, DeferredStatus, Error};
+
+#[hook_module("not_a_real_module.dll")]
+mod attach {
+    #[hook(AttachHook, symbol = "Foo")]
+    fn attach_hook() -> i32 {
+        AttachHook.call()
+    }
+}
+
+#[hook_module("not_a_real_module.dll", load_if_missing)]
+mod load {
+    #[hook(LoadHook, symbol = "Foo")]
+    fn load_hook() -> i32 {
+        LoadHook.call()
+    }
+}
+
+#[hook_module("not_a_real_module.dll", deferred)]
+mod deferred {
+    #[hook(DeferredHook, symbol = "Foo")]
+    fn deferred_hook() -> i32 {
+        DeferredHook.call()
+    }
+}
+
+fn main() {
+    // Neither mode can find a module that doesn't exist
+    for result in unsafe { [attach::init_detours(), load::init_detours()] } {
+        let Err(Error::InitFailed(report)) = result else {
+            panic!("missing module should fail");
+        };
+        assert_eq!(report.hooks.len(), 1);
+        assert!(matches!(report.hooks[0].outcome, Err(Error::ModuleNotLoaded)));
+    }
+
+    // Deferred hooks wait for the module to be loaded instead
+    assert!(unsafe { deferred::init_detours() }.is_ok());
+    assert_eq!(
+        retour_utils::deferred_status(deferred::MODULE_NAME),
+        Some(DeferredStatus::Pending)
+    );
+}
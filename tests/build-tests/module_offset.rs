@@ -0,0 +1,54 @@
+use std::ffi::{c_char, c_void};
+
+use retour_utils::{LookupData, ModuleInfo, ModuleLoad};
+
+#[path = "common/process.rs"]
+mod process;
+
+/// Base address the loader reports for the module containing `addr`
+#[cfg(unix)]
+fn loader_base(addr: usize) -> usize {
+    #[repr(C)]
+    struct DlInfo {
+        dli_fname: *const c_char,
+        dli_fbase: *mut c_void,
+        dli_sname: *const c_char,
+        dli_saddr: *mut c_void,
+    }
+
+    extern "C" {
+        fn dladdr(addr: *const c_void, info: *mut DlInfo) -> i32;
+    }
+
+    let mut info: DlInfo = unsafe { std::mem::zeroed() };
+    assert_ne!(unsafe { dladdr(addr as *const c_void, &mut info) }, 0);
+    info.dli_fbase as usize
+}
+
+/// Base address the loader reports for the module containing `addr`
+#[cfg(windows)]
+fn loader_base(_addr: usize) -> usize {
+    #[link(name = "kernel32")]
+    extern "system" {
+        fn GetModuleHandleA(module_name: *const c_char) -> *mut c_void;
+    }
+
+    let name = std::ffi::CString::new(process::LIB).unwrap();
+    unsafe { GetModuleHandleA(name.as_ptr()) as usize }
+}
+
+fn main() {
+    let process_id = LookupData::from_symbol(process::LIB, process::PROCESS_ID)
+        .resolve(ModuleLoad::Attach)
+        .unwrap() as usize;
+    let module = ModuleInfo::find(process::LIB).unwrap();
+    assert_eq!(module.base, loader_base(process_id));
+    assert!((module.base..module.base + module.size).contains(&process_id));
+
+    // An offset from the module's base resolves back to the same function
+    let offset = process_id - module.base;
+    let resolved = LookupData::from_offset(process::LIB, offset)
+        .resolve(ModuleLoad::Attach)
+        .unwrap();
+    assert_eq!(resolved as usize, process_id);
+}
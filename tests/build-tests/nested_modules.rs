@@ -0,0 +1,67 @@
+use retour_utils::hook_module;
+
+#[path = "common/process.rs"]
+#[macro_use]
+mod process;
+
+macro_rules! define_hooks {
+    ($lib:literal, $process_id:literal, $thread_id:literal) => {
+        #[hook_module($lib)]
+        mod hooks {
+            use crate::process::Id;
+
+            #[hook(pub unsafe extern "system" ProcessIdHook, symbol = $process_id)]
+            fn process_id_hook() -> Id {
+                unsafe { ProcessIdHook.call() }
+            }
+
+            pub mod threads {
+                use crate::process::Id;
+                // Resolved from this module, not the parent
+                use super::Never;
+
+                #[hook(pub unsafe extern "system" ThreadIdHook, symbol = $thread_id, group = "tid")]
+                fn thread_id_hook() -> Id {
+                    let _: Option<Never> = None;
+                    unsafe { ThreadIdHook.call() }
+                }
+            }
+
+            pub enum Never {}
+        }
+    };
+}
+
+with_process_lib!(define_hooks);
+
+fn main() {
+    unsafe {
+        // The parent's functions chain into the nested module's
+        let report = hooks::init_detours().unwrap();
+        let detour_names: Vec<_> = report.hooks.iter().map(|hook| hook.detour_name).collect();
+        assert_eq!(detour_names, ["ProcessIdHook", "ThreadIdHook"]);
+        assert!(hooks::threads::ThreadIdHook.is_enabled());
+
+        hooks::disable_detours().unwrap();
+        assert!(!hooks::is_enabled());
+        hooks::enable_group("tid").unwrap();
+        assert!(!hooks::ProcessIdHook.is_enabled());
+        assert!(hooks::threads::ThreadIdHook.is_enabled());
+        assert!(hooks::is_enabled());
+
+        // The nested module can also be handled on its own
+        hooks::threads::disable_detours().unwrap();
+        assert!(!hooks::is_enabled());
+        hooks::enable_detours().unwrap();
+        assert!(hooks::threads::is_enabled());
+        hooks::disable_detours().unwrap();
+    }
+
+    assert_eq!(hooks::HOOKS.len(), 1);
+    assert_eq!(hooks::threads::HOOKS.len(), 1);
+    let paths: Vec<_> = retour_utils::hook_modules()
+        .iter()
+        .map(|module| module.path.rsplit("::").next().unwrap())
+        .collect();
+    assert!(paths.contains(&"hooks") && paths.contains(&"threads"));
+}
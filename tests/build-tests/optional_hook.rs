@@ -0,0 +1,49 @@
+use retour_utils::{hook_module, Error};
+
+#[path = "common/process.rs"]
+#[macro_use]
+mod process;
+
+macro_rules! define_hooks {
+    ($lib:literal, $process_id:literal, $thread_id:literal) => {
+        #[hook_module($lib)]
+        mod hooks {
+            use crate::process::Id;
+
+            #[hook(pub unsafe extern "system" ProcessIdHook, symbol = $process_id)]
+            fn process_id_hook() -> Id {
+                unsafe { ProcessIdHook.call() }
+            }
+
+            #[hook(MissingHook, symbol = "retour_utils_missing_symbol", optional)]
+            fn missing_hook() {
+                MissingHook.call()
+            }
+        }
+    };
+}
+
+with_process_lib!(define_hooks);
+
+#[hook_module("foo.dll")]
+mod missing_module {
+    // A module that isn't loaded still fails, even for optional hooks
+    #[hook(OptionalHook, symbol = "Foo", optional)]
+    fn optional_hook() {
+        OptionalHook.call()
+    }
+}
+
+fn main() {
+    let report = unsafe { hooks::init_detours() }.unwrap();
+    let skipped: Vec<_> = report.skipped().map(|hook| hook.detour_name).collect();
+    assert_eq!(skipped, ["MissingHook"]);
+    assert_eq!(report.failures().count(), 0);
+    assert!(hooks::ProcessIdHook.is_enabled());
+    unsafe { hooks::disable_detours() }.unwrap();
+
+    let Err(Error::InitFailed(report)) = (unsafe { missing_module::init_detours() }) else {
+        panic!("missing module should fail");
+    };
+    assert_eq!(report.skipped().count(), 0);
+}
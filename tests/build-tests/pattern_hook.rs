@@ -0,0 +1,67 @@
+use retour_utils::hook_module;
+
+#[path = "common/process.rs"]
+mod process;
+
+#[hook_module("some_lib.dll")]
+mod hooks {
+    #[hook(PatternHook, pattern = "48 89 5C 24 ?? 57 48 83 EC 20")]
+    fn pattern_hook(a: i32) -> i32 {
+        PatternHook.call(a)
+    }
+
+    #[hook(unsafe extern "C" SingleWildcardHook, pattern = "E8 ? ? ? ? 48 8b d8")]
+    fn single_wildcard_hook() {
+        unsafe { SingleWildcardHook.call() }
+    }
+
+    #[hook(FollowCallHook, pattern = "E8 ?? ?? ?? ?? 48 8B D8", follow = rel32(1))]
+    fn follow_call_hook(a: i32) -> i32 {
+        FollowCallHook.call(a)
+    }
+
+    #[hook(FollowLeaHook, pattern = "48 8D 0D ?? ?? ?? ?? E8", follow = rel32(3))]
+    fn follow_lea_hook() {
+        FollowLeaHook.call()
+    }
+}
+
+// needed for trybuild
+fn main() {
+    let _ = retour_utils::LookupData::from_pattern("some_lib.dll", "48 89 5C 24 ?? 57");
+    let _ = retour_utils::LookupData::from_pattern_follow(
+        "some_lib.dll",
+        "E8 ?? ?? ?? ?? 48 8B D8",
+        retour_utils::Follow::Rel32(1),
+    );
+
+    // The displacement would be read past the end of the match
+    let past_pattern = retour_utils::LookupData::from_pattern_follow(
+        process::LIB,
+        "E8 ?? ?? ?? ??",
+        retour_utils::Follow::Rel32(40),
+    );
+    assert!(matches!(
+        past_pattern.resolve(retour_utils::ModuleLoad::Attach),
+        Err(retour_utils::Error::InvalidPattern)
+    ));
+
+    // The first bytes of the process id function, with a wildcard, only match that function
+    let process_id = retour_utils::LookupData::from_symbol(process::LIB, process::PROCESS_ID)
+        .resolve(retour_utils::ModuleLoad::Attach)
+        .unwrap();
+    let bytes = unsafe { std::slice::from_raw_parts(process_id as *const u8, 32) };
+    let pattern: Vec<_> = bytes
+        .iter()
+        .enumerate()
+        .map(|(i, byte)| match i {
+            1 => "??".to_string(),
+            _ => format!("{byte:02X}"),
+        })
+        .collect();
+    let pattern: &'static str = pattern.join(" ").leak();
+    let found = retour_utils::LookupData::from_pattern(process::LIB, pattern)
+        .resolve(retour_utils::ModuleLoad::Attach)
+        .unwrap();
+    assert_eq!(found, process_id);
+}
@@ -0,0 +1,49 @@
+use retour_utils::hook_module;
+
+mod ffi {
+    use std::ffi::c_int;
+
+    #[repr(C)]
+    pub struct LuaState {
+        _private: [u8; 0],
+    }
+
+    extern "C" {
+        pub fn abs(n: c_int) -> c_int;
+    }
+
+    pub unsafe extern "C" fn lua_settop(_l: *mut LuaState, _idx: c_int) {}
+
+    pub unsafe extern "C" fn lua_tolstring(
+        _l: *mut LuaState,
+        _idx: c_int,
+        _len: *mut usize,
+    ) -> *const u8 {
+        std::ptr::null()
+    }
+}
+
+#[hook_module("lua52.dll")]
+mod hooks {
+    use crate::ffi::LuaState;
+    use std::ffi::c_int;
+
+    #[hook(unsafe extern "C", check = crate::ffi::abs)]
+    fn abs(n: c_int) -> c_int {
+        unsafe { abs_Detour.call(n) }
+    }
+
+    #[hook(unsafe extern "C" Settop, symbol = "lua_settop", check = super::ffi::lua_settop)]
+    fn settop(l: *mut LuaState, idx: c_int) {
+        unsafe { Settop.call(l, idx) }
+    }
+
+    // Borrows are checked as the pointers the `StaticDetour` takes
+    #[hook(unsafe extern "C", check = crate::ffi::lua_tolstring)]
+    fn lua_tolstring(l: &mut LuaState, idx: c_int, len: Option<&mut usize>) -> *const u8 {
+        let len = len.map_or(std::ptr::null_mut(), std::ptr::from_mut);
+        unsafe { lua_tolstring_Detour.call(l, idx, len) }
+    }
+}
+
+fn main() {}
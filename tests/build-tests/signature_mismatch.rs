@@ -0,0 +1,33 @@
+use retour_utils::hook_module;
+
+mod ffi {
+    use std::ffi::c_int;
+
+    pub unsafe extern "C" fn lua_settop(_l: *mut u8, _idx: c_int) {}
+
+    pub extern "C" fn lua_gettop(_l: *mut u8) -> c_int {
+        0
+    }
+}
+
+#[hook_module("lua52.dll")]
+mod hooks {
+    #[hook(unsafe extern "C" WrongArg, symbol = "lua_settop", check = crate::ffi::lua_settop)]
+    fn wrong_arg(_l: *mut u8, _idx: u64) {}
+
+    #[hook(unsafe extern "system" WrongAbi, symbol = "lua_settop_abi", check = crate::ffi::lua_settop)]
+    fn wrong_abi(_l: *mut u8, _idx: i32) {}
+
+    #[hook(unsafe extern "C" WrongReturn, symbol = "lua_settop_ret", check = crate::ffi::lua_settop)]
+    fn wrong_return(_l: *mut u8, _idx: i32) -> i32 {
+        0
+    }
+
+    // A safe function would coerce to the `unsafe` function type
+    #[hook(unsafe extern "C" WrongUnsafety, symbol = "lua_gettop", check = crate::ffi::lua_gettop)]
+    fn wrong_unsafety(_l: *mut u8) -> i32 {
+        0
+    }
+}
+
+fn main() {}
@@ -0,0 +1,66 @@
+use retour_utils::{hook, hook_module};
+
+#[path = "common/process.rs"]
+#[macro_use]
+mod process;
+
+macro_rules! define_hooks {
+    ($lib:literal, $process_id:literal, $thread_id:literal) => {
+        mod process_hooks {
+            use crate::process::Id;
+            use retour_utils::hook;
+
+            #[hook(module = $lib, pub unsafe extern "system" ProcessIdHook, symbol = $process_id)]
+            fn process_id_hook() -> Id {
+                unsafe { ProcessIdHook.call() }
+            }
+        }
+
+        // Derives the detour name like `#[hook]` in a `#[hook_module]`
+        #[hook(module = $lib, unsafe extern "system", symbol = $thread_id)]
+        fn thread_id() -> process::Id {
+            unsafe { thread_id_Detour.call() }
+        }
+    };
+}
+
+with_process_lib!(define_hooks);
+
+#[hook_module("foo.dll")]
+mod missing {
+    #[hook(symbol = "Foo")]
+    fn foo() {
+        foo_Detour.call()
+    }
+
+    mod nested {
+        #[hook(symbol = "Bar")]
+        fn bar() {
+            bar_Detour.call()
+        }
+    }
+}
+
+fn main() {
+    assert_eq!(retour_utils::registered_hooks().len(), 2);
+    assert!(retour_utils::registered_hooks()
+        .iter()
+        .any(|registered| registered.path.ends_with("::process_hooks")));
+
+    // Every standalone hook and every hook module, with nested modules initialized by their parent
+    let Err(retour_utils::Error::InitFailed(report)) = (unsafe { retour_utils::init_all() }) else {
+        panic!("foo.dll isn't loaded");
+    };
+    let mut detour_names: Vec<_> = report.hooks.iter().map(|hook| hook.detour_name).collect();
+    detour_names.sort();
+    let mut expected = ["ProcessIdHook", "thread_id_Detour", "foo_Detour", "bar_Detour"];
+    expected.sort();
+    assert_eq!(detour_names, expected);
+    assert_eq!(report.failures().count(), 2);
+    assert!(process_hooks::ProcessIdHook.is_enabled());
+
+    let registered = &retour_utils::registered_hooks()[0];
+    assert!(registered.hook.is_enabled());
+    assert!(registered.hook.address().is_some());
+    unsafe { process_hooks::ProcessIdHook.disable() }.unwrap();
+}
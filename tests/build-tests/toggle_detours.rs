@@ -0,0 +1,47 @@
+use retour_utils::hook_module;
+
+#[path = "common/process.rs"]
+#[macro_use]
+mod process;
+
+macro_rules! define_hooks {
+    ($lib:literal, $process_id:literal, $thread_id:literal) => {
+        #[hook_module($lib)]
+        mod hooks {
+            use crate::process::Id;
+
+            #[hook(unsafe extern "system" ProcessIdHook, symbol = $process_id)]
+            fn process_id_hook() -> Id {
+                unsafe { ProcessIdHook.call() }
+            }
+        }
+    };
+}
+
+with_process_lib!(define_hooks);
+
+#[hook_module("foo.dll")]
+mod never_initialized {
+    #[hook(NeverInitializedHook, symbol = "Foo")]
+    fn never_initialized_hook() {
+        NeverInitializedHook.call()
+    }
+}
+
+fn main() {
+    unsafe {
+        assert!(!hooks::is_enabled());
+        hooks::init_detours().unwrap();
+        assert!(hooks::is_enabled());
+
+        hooks::disable_detours().unwrap();
+        assert!(!hooks::is_enabled());
+        hooks::enable_detours().unwrap();
+        assert!(hooks::is_enabled());
+        hooks::disable_detours().unwrap();
+
+        // Hooks that were never initialized are skipped
+        never_initialized::enable_detours().unwrap();
+        assert!(!never_initialized::is_enabled());
+    }
+}
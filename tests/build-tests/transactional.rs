@@ -0,0 +1,37 @@
+use retour_utils::{hook_module, Error};
+
+#[path = "common/process.rs"]
+#[macro_use]
+mod process;
+
+macro_rules! define_hooks {
+    ($lib:literal, $process_id:literal, $thread_id:literal) => {
+        #[hook_module($lib, transactional)]
+        mod hooks {
+            use crate::process::Id;
+
+            #[hook(pub unsafe extern "system" InstalledHook, symbol = $process_id)]
+            fn installed_hook() -> Id {
+                unsafe { InstalledHook.call() }
+            }
+
+            #[hook(MissingHook, symbol = "retour_utils_missing_symbol")]
+            fn missing_hook() {
+                MissingHook.call()
+            }
+        }
+    };
+}
+
+with_process_lib!(define_hooks);
+
+fn main() {
+    let Err(Error::InitFailed(report)) = (unsafe { hooks::init_detours() }) else {
+        panic!("missing symbol should fail");
+    };
+    // The hook that was installed is disabled again because the other one failed
+    assert!(report.hooks[0].address.is_some());
+    assert!(matches!(report.hooks[0].outcome, Err(Error::RolledBack)));
+    assert!(matches!(report.hooks[1].outcome, Err(Error::SymbolNotFound)));
+    assert!(!hooks::InstalledHook.is_enabled());
+}
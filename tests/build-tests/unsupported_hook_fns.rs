@@ -0,0 +1,57 @@
+use retour_utils::hook_module;
+
+pub struct Wrapper<'a>(&'a i32);
+
+#[hook_module("foo.dll")]
+mod hooks {
+    use super::Wrapper;
+
+    #[hook(AsyncHook, symbol = "Async")]
+    async fn async_hook() {}
+
+    #[hook(ConstHook, symbol = "Const")]
+    const fn const_hook() {}
+
+    #[hook(GenericHook, symbol = "Generic")]
+    fn generic_hook<T>(_: T) {}
+
+    #[hook(extern "vectorcall" AbiHook, symbol = "Abi")]
+    fn abi_hook() {}
+
+    #[hook(BorrowHook, symbol = "Borrow")]
+    fn borrow_hook<'a>(_: Wrapper<'a>) {}
+
+    extern "C" {
+        fn declared();
+    }
+
+    #[hook(extern "system" declared)]
+    fn declared_abi_hook() {}
+
+    #[hook(SafeBorrowHook, symbol = "SafeBorrow")]
+    fn safe_borrow_hook(_: &i32, _: Option<&mut i32>) -> &'static i32 {
+        &0
+    }
+
+    #[hook(unsafe LateCtxHook, symbol = "LateCtx")]
+    fn late_ctx_hook(_: i32, _: &retour_utils::HookCtx<fn(i32)>) {}
+
+    pub struct Foo;
+
+    impl Foo {
+        #[hook(ByValueHook, symbol = "ByValue")]
+        fn by_value_hook(self) {}
+
+        #[hook(SafeMethodHook, symbol = "SafeMethod")]
+        fn safe_method_hook(&mut self) {}
+    }
+
+    pub struct Bar<T>(T);
+
+    impl<T> Bar<T> {
+        #[hook(unsafe GenericImplHook, symbol = "GenericImpl")]
+        fn generic_impl_hook(&self) {}
+    }
+}
+
+fn main() {}
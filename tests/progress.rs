@@ -3,7 +3,43 @@ fn tests() {
     let t = trybuild::TestCases::new();
     t.pass("tests/build-tests/retain_other_items.rs");
     t.compile_fail("tests/build-tests/require_module_name.rs");
-    t.pass("tests/build-tests/maintain_vis.rs")
+    t.pass("tests/build-tests/maintain_vis.rs");
+    t.pass("tests/build-tests/module_load.rs");
+    t.pass("tests/build-tests/deferred_install.rs");
+    t.pass("tests/build-tests/arg_patterns.rs");
+    t.pass("tests/build-tests/signature_check.rs");
+    t.pass("tests/build-tests/cfg_hooks.rs");
+    t.compile_fail("tests/build-tests/unknown_module_option.rs");
+    t.compile_fail("tests/build-tests/conflicting_module_options.rs");
+    t.compile_fail("tests/build-tests/malformed_hook_args.rs");
+    t.compile_fail("tests/build-tests/duplicate_hooks.rs");
+    t.compile_fail("tests/build-tests/unsupported_hook_fns.rs");
+    t.compile_fail("tests/build-tests/signature_mismatch.rs");
+}
+
+// Hook functions of a library every process has loaded, see `build-tests/common/process.rs`
+#[cfg(any(windows, all(target_os = "linux", target_env = "gnu")))]
+#[test]
+fn hook_process_functions() {
+    let t = trybuild::TestCases::new();
+    t.pass("tests/build-tests/pattern_hook.rs");
+    t.pass("tests/build-tests/module_offset.rs");
+    t.pass("tests/build-tests/init_report.rs");
+    t.pass("tests/build-tests/transactional.rs");
+    t.pass("tests/build-tests/toggle_detours.rs");
+    t.pass("tests/build-tests/optional_hook.rs");
+    t.pass("tests/build-tests/hook_groups.rs");
+    t.pass("tests/build-tests/hook_registry.rs");
+    t.pass("tests/build-tests/hook_module_trait.rs");
+    t.pass("tests/build-tests/method_hook.rs");
+    t.pass("tests/build-tests/borrowed_args.rs");
+    t.pass("tests/build-tests/derived_names.rs");
+    t.pass("tests/build-tests/extern_declarations.rs");
+    t.pass("tests/build-tests/nested_modules.rs");
+    t.pass("tests/build-tests/standalone_hooks.rs");
+    t.pass("tests/build-tests/hook_set.rs");
+    t.pass("tests/build-tests/closure_hook.rs");
+    t.pass("tests/build-tests/hook_ctx.rs");
 }
 
 #[test]